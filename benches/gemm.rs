@@ -0,0 +1,49 @@
+//! Compares `Matrix::dot` with the reference triple loop `Matrix::_dot`.
+//!
+//! Run with `cargo bench --bench gemm`.
+
+use std::time::{Duration, Instant};
+
+use feoho_nn::Matrix;
+use rand::{rngs::StdRng, SeedableRng};
+
+/// Best time of `runs` calls, the minimum is the least disturbed by other processes.
+fn best_of(runs: usize, mut f: impl FnMut()) -> Duration {
+    (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn gflops(n: usize, time: Duration) -> f64 {
+    2.0 * (n * n * n) as f64 / time.as_secs_f64() / 1e9
+}
+
+fn bench(n: usize, runs: usize) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut a = Matrix::zero(n, n);
+    let mut b = Matrix::zero(n, n);
+    a.random_range_with(-1.0..1.0, &mut rng);
+    b.random_range_with(-1.0..1.0, &mut rng);
+    let mut c = Matrix::zero(n, n);
+
+    let naive = best_of(runs, || Matrix::_dot(&mut c, &a, &b));
+    let fast = best_of(runs, || c.dot(&a, &b));
+    println!(
+        "{n:>5}x{n:<5} naive {:>10.3?} ({:>6.2} GFLOP/s)   dot {:>10.3?} ({:>6.2} GFLOP/s)   speedup {:>6.1}x",
+        naive,
+        gflops(n, naive),
+        fast,
+        gflops(n, fast),
+        naive.as_secs_f64() / fast.as_secs_f64(),
+    );
+}
+
+fn main() {
+    bench(256, 5);
+    bench(1024, 1);
+}
@@ -1,33 +1,187 @@
-use crate::NNET;
+mod vector;
 
+pub use vector::*;
+
+use std::{fmt, str::FromStr};
+
+use crate::{Float, NNET};
+
+/// The `_in` functions work on any element type, `activate` and `derivative`
+/// are their `f64` versions, so that e.g. `Sigmoid::activate(0.5)` needs no annotation.
+///
+/// Implement `activate_in` and `derivative_in` for a custom function,
+/// an `Arch` with it as activation type calls them directly.
+/// A function that only implements `activate` and `derivative` keeps working,
+/// the `_in` functions then go through `f64`. Each pair defaults to the other,
+/// so one of them has to be implemented.
 pub trait ActivationFunction {
-    fn activate(x: NNET) -> NNET;
-    fn derivative(x: NNET) -> NNET;
+    /// Runtime tag of a built-in function, used by `Arch` to save it by name.
+    /// `None` for a custom function.
+    const ACTIVATION: Option<Activation> = None;
+
+    fn activate_in<T: Float>(x: T) -> T {
+        T::from_f64(Self::activate(x.as_f64()))
+    }
+
+    fn derivative_in<T: Float>(x: T) -> T {
+        T::from_f64(Self::derivative(x.as_f64()))
+    }
+
+    fn activate(x: NNET) -> NNET {
+        Self::activate_in(x)
+    }
+
+    fn derivative(x: NNET) -> NNET {
+        Self::derivative_in(x)
+    }
+}
+
+/// Runtime counterpart of the activation types,
+/// lets each layer of an `Arch` use a different function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Activation {
+    Sigmoid,
+    ReLU,
+    Tanh,
+    LeakyReLU,
+    Softplus,
+    Swish,
+    Linear,
+    Softmax,
+    LogSoftmax,
+    /// Maximum over groups of the given number of pieces.
+    Maxout(usize),
+}
+
+impl Activation {
+    /// Number of weighted sums feeding each output unit, more than one only for `Maxout`.
+    pub fn pieces(&self) -> usize {
+        match self {
+            Self::Maxout(k) => *k,
+            _ => 1,
+        }
+    }
+
+    /// Whether every output only depends on its own weighted sum, false for
+    /// `Softmax`, `LogSoftmax` and `Maxout`, which work on the whole row.
+    pub fn is_element_wise(&self) -> bool {
+        !matches!(self, Self::Softmax | Self::LogSoftmax | Self::Maxout(_))
+    }
+
+    /// Applies the activation to the weighted sums `z`, writing the result into `a`.
+    pub fn activate_row(&self, z: &[NNET], a: &mut [NNET]) {
+        self.activate_row_in(z, a)
+    }
+
+    /// Jacobian-vector product, see `VectorActivation::backward_row`.
+    pub fn backward_row(&self, z: &[NNET], a: &[NNET], da: &[NNET], dz: &mut [NNET]) {
+        self.backward_row_in(z, a, da, dz)
+    }
+
+    /// Like `activate_row`, for any element type.
+    pub fn activate_row_in<T: Float>(&self, z: &[T], a: &mut [T]) {
+        match self {
+            Self::Sigmoid => Sigmoid::activate_row_in(z, a),
+            Self::ReLU => ReLU::activate_row_in(z, a),
+            Self::Tanh => Tanh::activate_row_in(z, a),
+            Self::LeakyReLU => LeakyReLU::activate_row_in(z, a),
+            Self::Softplus => Softplus::activate_row_in(z, a),
+            Self::Swish => Swish::activate_row_in(z, a),
+            Self::Linear => Linear::activate_row_in(z, a),
+            Self::Softmax => Softmax::activate_row_in(z, a),
+            Self::LogSoftmax => LogSoftmax::activate_row_in(z, a),
+            Self::Maxout(k) => vector::maxout(*k, z, a),
+        }
+    }
+
+    /// Like `backward_row`, for any element type.
+    pub fn backward_row_in<T: Float>(&self, z: &[T], a: &[T], da: &[T], dz: &mut [T]) {
+        match self {
+            Self::Sigmoid => Sigmoid::backward_row_in(z, a, da, dz),
+            Self::ReLU => ReLU::backward_row_in(z, a, da, dz),
+            Self::Tanh => Tanh::backward_row_in(z, a, da, dz),
+            Self::LeakyReLU => LeakyReLU::backward_row_in(z, a, da, dz),
+            Self::Softplus => Softplus::backward_row_in(z, a, da, dz),
+            Self::Swish => Swish::backward_row_in(z, a, da, dz),
+            Self::Linear => Linear::backward_row_in(z, a, da, dz),
+            Self::Softmax => Softmax::backward_row_in(z, a, da, dz),
+            Self::LogSoftmax => LogSoftmax::backward_row_in(z, a, da, dz),
+            Self::Maxout(k) => vector::maxout_backward(*k, z, da, dz),
+        }
+    }
+}
+
+/// Names used when an `Arch` is saved, e.g. `relu` or `maxout(2)`.
+impl fmt::Display for Activation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sigmoid => write!(f, "sigmoid"),
+            Self::ReLU => write!(f, "relu"),
+            Self::Tanh => write!(f, "tanh"),
+            Self::LeakyReLU => write!(f, "leaky_relu"),
+            Self::Softplus => write!(f, "softplus"),
+            Self::Swish => write!(f, "swish"),
+            Self::Linear => write!(f, "linear"),
+            Self::Softmax => write!(f, "softmax"),
+            Self::LogSoftmax => write!(f, "log_softmax"),
+            Self::Maxout(k) => write!(f, "maxout({})", k),
+        }
+    }
+}
+
+impl FromStr for Activation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let activation = match s {
+            "sigmoid" => Self::Sigmoid,
+            "relu" => Self::ReLU,
+            "tanh" => Self::Tanh,
+            "leaky_relu" => Self::LeakyReLU,
+            "softplus" => Self::Softplus,
+            "swish" => Self::Swish,
+            "linear" => Self::Linear,
+            "softmax" => Self::Softmax,
+            "log_softmax" => Self::LogSoftmax,
+            _ => {
+                let k = s
+                    .strip_prefix("maxout(")
+                    .and_then(|s| s.strip_suffix(')'))
+                    .and_then(|k| k.parse().ok())
+                    .filter(|k| *k > 0)
+                    .ok_or_else(|| format!("unknown activation `{}`", s))?;
+                Self::Maxout(k)
+            }
+        };
+        Ok(activation)
+    }
 }
 
 /// Sigmoid Activation Function
 pub struct Sigmoid;
 impl ActivationFunction for Sigmoid {
-    fn activate(x: NNET) -> NNET {
-        1.0 / (1.0 + (-x).exp())
+    const ACTIVATION: Option<Activation> = Some(Activation::Sigmoid);
+    fn activate_in<T: Float>(x: T) -> T {
+        T::one() / (T::one() + (-x).exp())
     }
-    fn derivative(x: NNET) -> NNET {
-        let sigmoid_x = Self::activate(x);
-        sigmoid_x * (1.0 - sigmoid_x)
+    fn derivative_in<T: Float>(x: T) -> T {
+        let sigmoid_x = Self::activate_in(x);
+        sigmoid_x * (T::one() - sigmoid_x)
     }
 }
 
 /// ReLU
 pub struct ReLU;
 impl ActivationFunction for ReLU {
-    fn activate(x: NNET) -> NNET {
-        x.max(0.0)
+    const ACTIVATION: Option<Activation> = Some(Activation::ReLU);
+    fn activate_in<T: Float>(x: T) -> T {
+        x.max(T::zero())
     }
-    fn derivative(x: NNET) -> NNET {
-        if x > 0.0 {
-            1.0
+    fn derivative_in<T: Float>(x: T) -> T {
+        if x > T::zero() {
+            T::one()
         } else {
-            0.0
+            T::zero()
         }
     }
 }
@@ -35,28 +189,30 @@ impl ActivationFunction for ReLU {
 /// Tanh
 pub struct Tanh;
 impl ActivationFunction for Tanh {
-    fn activate(x: NNET) -> NNET {
+    const ACTIVATION: Option<Activation> = Some(Activation::Tanh);
+    fn activate_in<T: Float>(x: T) -> T {
         x.tanh()
     }
-    fn derivative(x: NNET) -> NNET {
-        1.0 - x.tanh().powi(2)
+    fn derivative_in<T: Float>(x: T) -> T {
+        T::one() - x.tanh().powi(2)
     }
 }
 /// Leaky ReLU
 pub struct LeakyReLU;
 impl ActivationFunction for LeakyReLU {
-    fn activate(x: NNET) -> NNET {
-        if x > 0.0 {
+    const ACTIVATION: Option<Activation> = Some(Activation::LeakyReLU);
+    fn activate_in<T: Float>(x: T) -> T {
+        if x > T::zero() {
             x
         } else {
-            0.01 * x
+            T::from_f64(0.01) * x
         }
     }
-    fn derivative(x: NNET) -> NNET {
-        if x > 0.0 {
-            1.0
+    fn derivative_in<T: Float>(x: T) -> T {
+        if x > T::zero() {
+            T::one()
         } else {
-            0.01
+            T::from_f64(0.01)
         }
     }
 }
@@ -64,22 +220,36 @@ impl ActivationFunction for LeakyReLU {
 /// Softplus
 pub struct Softplus;
 impl ActivationFunction for Softplus {
-    fn activate(x: NNET) -> NNET {
-        (1.0 + x.exp()).ln()
+    const ACTIVATION: Option<Activation> = Some(Activation::Softplus);
+    fn activate_in<T: Float>(x: T) -> T {
+        (T::one() + x.exp()).ln()
     }
-    fn derivative(x: NNET) -> NNET {
-        1.0 / (1.0 + (-x).exp())
+    fn derivative_in<T: Float>(x: T) -> T {
+        T::one() / (T::one() + (-x).exp())
     }
 }
 
 /// Swish
 pub struct Swish;
 impl ActivationFunction for Swish {
-    fn activate(x: NNET) -> NNET {
-        x / (1.0 + (-x).exp()) // x * sigmoid(x)
+    const ACTIVATION: Option<Activation> = Some(Activation::Swish);
+    fn activate_in<T: Float>(x: T) -> T {
+        x / (T::one() + (-x).exp()) // x * sigmoid(x)
     }
-    fn derivative(x: NNET) -> NNET {
-        let sigmoid_x = 1.0 / (1.0 + (-x).exp());
-        sigmoid_x + x * sigmoid_x * (1.0 - sigmoid_x)
+    fn derivative_in<T: Float>(x: T) -> T {
+        let sigmoid_x = T::one() / (T::one() + (-x).exp());
+        sigmoid_x + x * sigmoid_x * (T::one() - sigmoid_x)
+    }
+}
+
+/// Linear, i.e. identity. Used on the output layer for regression.
+pub struct Linear;
+impl ActivationFunction for Linear {
+    const ACTIVATION: Option<Activation> = Some(Activation::Linear);
+    fn activate_in<T: Float>(x: T) -> T {
+        x
+    }
+    fn derivative_in<T: Float>(_x: T) -> T {
+        T::one()
     }
 }
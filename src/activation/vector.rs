@@ -0,0 +1,151 @@
+//! Activations that work on a whole row of a `Matrix` instead of element by element.
+
+use std::marker::PhantomData;
+
+use crate::{
+    float::{copy_from_f64, to_f64_vec},
+    Activation, ActivationFunction, Float, NNET,
+};
+
+/// Like `ActivationFunction`, the `_in` functions work on any element type
+/// and `activate_row` and `backward_row` are their `f64` versions.
+///
+/// A custom row-wise function has to write one output per weighted sum.
+/// Like for `ActivationFunction`, implementing only the `f64` functions is enough.
+pub trait VectorActivation {
+    /// Runtime tag of a built-in function, used by `Arch` to save it by name.
+    /// `None` for a custom function.
+    const ACTIVATION: Option<Activation> = None;
+
+    /// Whether every output only depends on its own weighted sum, see `Activation::is_element_wise`.
+    const ELEMENT_WISE: bool = false;
+
+    /// Applies the activation to the weighted sums `z`, writing the result into `a`.
+    fn activate_row_in<T: Float>(z: &[T], a: &mut [T]) {
+        let mut out = vec![0.0; a.len()];
+        Self::activate_row(&to_f64_vec(z), &mut out);
+        copy_from_f64(a, &out);
+    }
+
+    /// Jacobian-vector product used by backpropagation:
+    /// given `da`, the derivative of the cost with respect to the outputs `a`,
+    /// writes the derivative with respect to the inputs `z` into `dz`.
+    fn backward_row_in<T: Float>(z: &[T], a: &[T], da: &[T], dz: &mut [T]) {
+        let mut out = vec![0.0; dz.len()];
+        Self::backward_row(&to_f64_vec(z), &to_f64_vec(a), &to_f64_vec(da), &mut out);
+        copy_from_f64(dz, &out);
+    }
+
+    fn activate_row(z: &[NNET], a: &mut [NNET]) {
+        Self::activate_row_in(z, a)
+    }
+
+    fn backward_row(z: &[NNET], a: &[NNET], da: &[NNET], dz: &mut [NNET]) {
+        Self::backward_row_in(z, a, da, dz)
+    }
+}
+
+/// Every element-wise activation is a vector activation with a diagonal Jacobian.
+impl<A: ActivationFunction> VectorActivation for A {
+    const ACTIVATION: Option<Activation> = <A as ActivationFunction>::ACTIVATION;
+    const ELEMENT_WISE: bool = true;
+
+    fn activate_row_in<T: Float>(z: &[T], a: &mut [T]) {
+        for (a, z) in a.iter_mut().zip(z) {
+            *a = A::activate_in(*z);
+        }
+    }
+
+    fn backward_row_in<T: Float>(z: &[T], _a: &[T], da: &[T], dz: &mut [T]) {
+        for ((dz, z), da) in dz.iter_mut().zip(z).zip(da) {
+            *dz = *da * A::derivative_in(*z);
+        }
+    }
+}
+
+/// Softmax, turns a row into a probability distribution.
+/// Pair with `CategoricalCrossEntropy`.
+pub struct Softmax;
+impl VectorActivation for Softmax {
+    const ACTIVATION: Option<Activation> = Some(Activation::Softmax);
+
+    fn activate_row_in<T: Float>(z: &[T], a: &mut [T]) {
+        // shift by the maximum so that exp does not overflow
+        let max = z.iter().cloned().fold(T::neg_infinity(), T::max);
+        let mut sum = T::zero();
+        for (a, z) in a.iter_mut().zip(z) {
+            *a = (*z - max).exp();
+            sum += *a;
+        }
+        for a in a.iter_mut() {
+            *a /= sum;
+        }
+    }
+
+    fn backward_row_in<T: Float>(_z: &[T], a: &[T], da: &[T], dz: &mut [T]) {
+        // J = diag(a) - a * a^T, so J * da = a * (da - a . da)
+        let dot: T = a.iter().zip(da).map(|(a, da)| *a * *da).sum();
+        for ((dz, a), da) in dz.iter_mut().zip(a).zip(da) {
+            *dz = *a * (*da - dot);
+        }
+    }
+}
+
+/// Logarithm of the softmax, computed without going through the probabilities.
+/// Pair with `NegativeLogLikelihood`.
+pub struct LogSoftmax;
+impl VectorActivation for LogSoftmax {
+    const ACTIVATION: Option<Activation> = Some(Activation::LogSoftmax);
+
+    fn activate_row_in<T: Float>(z: &[T], a: &mut [T]) {
+        let max = z.iter().cloned().fold(T::neg_infinity(), T::max);
+        let log_sum = z.iter().map(|z| (*z - max).exp()).sum::<T>().ln() + max;
+        for (a, z) in a.iter_mut().zip(z) {
+            *a = *z - log_sum;
+        }
+    }
+
+    fn backward_row_in<T: Float>(_z: &[T], a: &[T], da: &[T], dz: &mut [T]) {
+        // J = I - 1 * softmax^T, so J^T * da = da - softmax * sum(da)
+        let sum: T = da.iter().sum();
+        for ((dz, a), da) in dz.iter_mut().zip(a).zip(da) {
+            *dz = *da - a.exp() * sum;
+        }
+    }
+}
+
+/// Maxout, every output is the maximum of `K` consecutive weighted sums,
+/// so the layer before it produces `K` times as many values.
+pub struct Maxout<const K: usize = 2>(PhantomData<[(); K]>);
+impl<const K: usize> VectorActivation for Maxout<K> {
+    const ACTIVATION: Option<Activation> = Some(Activation::Maxout(K));
+
+    fn activate_row_in<T: Float>(z: &[T], a: &mut [T]) {
+        maxout(K, z, a);
+    }
+
+    fn backward_row_in<T: Float>(z: &[T], _a: &[T], da: &[T], dz: &mut [T]) {
+        maxout_backward(K, z, da, dz);
+    }
+}
+
+pub(super) fn maxout<T: Float>(k: usize, z: &[T], a: &mut [T]) {
+    assert_eq!(z.len(), a.len() * k, "ERROR: Maxout needs {} inputs per output.", k);
+    for (a, group) in a.iter_mut().zip(z.chunks(k)) {
+        *a = group.iter().cloned().fold(T::neg_infinity(), T::max);
+    }
+}
+
+/// Only the piece that won the maximum receives the gradient.
+pub(super) fn maxout_backward<T: Float>(k: usize, z: &[T], da: &[T], dz: &mut [T]) {
+    dz.fill(T::zero());
+    for (i, (group, da)) in z.chunks(k).zip(da).enumerate() {
+        let mut arg = 0;
+        for (j, z) in group.iter().enumerate() {
+            if *z > group[arg] {
+                arg = j;
+            }
+        }
+        dz[i * k + arg] = *da;
+    }
+}
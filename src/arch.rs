@@ -1,128 +1,722 @@
 //! for why the feed_forward issue
 //! read subtyping and variance in rust [rust nomicon](<https://doc.rust-lang.org/nomicon/subtyping.html> "Subtyping and Variance")
 
-use crate::{ActivationFunction, Matrix, Sigmoid, Tensor, NNET};
+use std::{fs, path::Path};
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{
+    format::{self, Decoder, Encoder},
+    Activation, Dataset, Float, GradientMethod, Initializer, Loss, NNError, Matrix, MatrixView, Mse, Optimizer, Result, Sgd, Tensor,
+    TrainConfig, VectorActivation, NNET,
+};
+
+/// Specification of one hidden layer passed to `Arch::new`.
+///
+/// A plain `usize` converts into a layer of that size that uses
+/// the `Arch` activation type, use `Layer::with` to pick another one.
+/// Row-wise activation types like `Softmax` only fit the output layer,
+/// with them every hidden layer needs `Layer::with`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layer {
+    pub size: usize,
+    /// `None` falls back to the activation type parameter of the `Arch`,
+    /// if that is element-wise, see `Activation::is_element_wise`.
+    pub activation: Option<Activation>,
+}
+
+impl Layer {
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            activation: None,
+        }
+    }
+
+    pub fn with(size: usize, activation: Activation) -> Self {
+        Self {
+            size,
+            activation: Some(activation),
+        }
+    }
+}
 
-pub struct Arch<A: ActivationFunction> {
+impl From<usize> for Layer {
+    fn from(size: usize) -> Self {
+        Self::new(size)
+    }
+}
+
+/// Neural network with activation `A` and loss `L`, computing in `T`, `f64` by default.
+pub struct Arch<A: VectorActivation, L: Loss = Mse, T: Float = NNET> {
     activation: std::marker::PhantomData<A>,
-    model: Tensor,
-    gradient: Tensor,
-    input: Matrix,
-    output: Matrix,
+    loss: std::marker::PhantomData<L>,
+    /// Activation of each layer, `activations[i]` produces `al[i + 1]`.
+    /// `None` is a layer that uses `A` through its trait functions, like the output layer.
+    activations: Vec<Option<Activation>>,
+    model: Tensor<T>,
+    gradient: Tensor<T>,
+    /// Dataset used by `train`, `cost` and `backprop`, see `Arch::set_dataset`
+    /// and the `_on` methods for other datasets.
+    data: Dataset<T>,
+    /// Source of every random operation of the network, see `Arch::with_seed`.
+    rng: StdRng,
 }
 
-impl<A: ActivationFunction> Arch<A> {
-    pub fn new(
-        data: &[NNET],
+impl<A: VectorActivation, L: Loss, T: Float> Arch<A, L, T> {
+    /// Creates a network with `Initializer::default()` weights.
+    pub fn new<H: Into<Layer> + Copy>(
+        data: &[T],
+        rows: usize,
+        input_cols: usize,
+        output_cols: usize,
+        hidden_layers: &[H],
+    ) -> Self {
+        Self::with_initializer(
+            data,
+            rows,
+            input_cols,
+            output_cols,
+            hidden_layers,
+            Initializer::default(),
+        )
+    }
+
+    /// Like `new`, with the weights and biases set up by `initializer`.
+    pub fn with_initializer<H: Into<Layer> + Copy>(
+        data: &[T],
+        rows: usize,
+        input_cols: usize,
+        output_cols: usize,
+        hidden_layers: &[H],
+        initializer: Initializer,
+    ) -> Self {
+        Self::try_from_rng(
+            data,
+            rows,
+            input_cols,
+            output_cols,
+            hidden_layers,
+            initializer,
+            StdRng::from_entropy(),
+        )
+        .unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `with_initializer`, but every random operation of this network,
+    /// the initial weights and the shuffling during training, is drawn from a generator
+    /// seeded with `seed`. Two networks with the same seed train bit for bit the same.
+    pub fn with_seed<H: Into<Layer> + Copy>(
+        data: &[T],
+        rows: usize,
+        input_cols: usize,
+        output_cols: usize,
+        hidden_layers: &[H],
+        initializer: Initializer,
+        seed: u64,
+    ) -> Self {
+        Self::try_from_rng(
+            data,
+            rows,
+            input_cols,
+            output_cols,
+            hidden_layers,
+            initializer,
+            StdRng::seed_from_u64(seed),
+        )
+        .unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `new`, but returns an error for an empty or too short dataset
+    /// and for layers without units instead of panicking.
+    pub fn try_new<H: Into<Layer> + Copy>(
+        data: &[T],
+        rows: usize,
+        input_cols: usize,
+        output_cols: usize,
+        hidden_layers: &[H],
+    ) -> Result<Self> {
+        Self::try_from_rng(
+            data,
+            rows,
+            input_cols,
+            output_cols,
+            hidden_layers,
+            Initializer::default(),
+            StdRng::from_entropy(),
+        )
+    }
+
+    fn try_from_rng<H: Into<Layer> + Copy>(
+        data: &[T],
         rows: usize,
         input_cols: usize,
         output_cols: usize,
-        hidden_layers: &[usize],
+        hidden_layers: &[H],
+        initializer: Initializer,
+        rng: StdRng,
+    ) -> Result<Self> {
+        if data.is_empty() || rows == 0 {
+            return Err(NNError::EmptyDataset);
+        }
+        if input_cols == 0 || output_cols == 0 {
+            return Err(NNError::InvalidLayerSpec(format!(
+                "{} input and {} output cols",
+                input_cols, output_cols
+            )));
+        }
+
+        // copy input and output values.
+        let data = Dataset::try_from_interleaved(data, rows, input_cols, output_cols)?;
+        Self::try_from_dataset_rng(data, hidden_layers, initializer, rng)
+    }
+
+    /// Creates a network with `Initializer::default()` weights whose input and output
+    /// layers fit `dataset`, which becomes the dataset of the network.
+    pub fn from_dataset<H: Into<Layer> + Copy>(dataset: Dataset<T>, hidden_layers: &[H]) -> Self {
+        Self::try_from_dataset(dataset, hidden_layers).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `from_dataset`, but returns an error for an empty dataset or layers without units.
+    pub fn try_from_dataset<H: Into<Layer> + Copy>(dataset: Dataset<T>, hidden_layers: &[H]) -> Result<Self> {
+        Self::try_from_dataset_rng(dataset, hidden_layers, Initializer::default(), StdRng::from_entropy())
+    }
+
+    /// Like `from_dataset`, seeded like `with_seed`.
+    pub fn from_dataset_with_seed<H: Into<Layer> + Copy>(
+        dataset: Dataset<T>,
+        hidden_layers: &[H],
+        initializer: Initializer,
+        seed: u64,
     ) -> Self {
-        assert_ne!(data.len(), 0);
-        assert_ne!(rows, 0);
-        assert_ne!(input_cols, 0);
-        assert_ne!(output_cols, 0);
+        Self::try_from_dataset_rng(dataset, hidden_layers, initializer, StdRng::seed_from_u64(seed))
+            .unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    fn try_from_dataset_rng<H: Into<Layer> + Copy>(
+        data: Dataset<T>,
+        hidden_layers: &[H],
+        initializer: Initializer,
+        mut rng: StdRng,
+    ) -> Result<Self> {
+        if data.is_empty() {
+            return Err(NNError::EmptyDataset);
+        }
+        let (input_cols, output_cols) = (data.get_input_col_count(), data.get_target_col_count());
+        if input_cols == 0 || output_cols == 0 {
+            return Err(NNError::InvalidLayerSpec(format!(
+                "{} input and {} output cols",
+                input_cols, output_cols
+            )));
+        }
 
         // setup layers add the input size and output size
-        let mut layers = hidden_layers.to_vec();
+        let hidden_layers: Vec<Layer> = hidden_layers.iter().map(|&l| l.into()).collect();
+        let mut layers: Vec<usize> = hidden_layers.iter().map(|l| l.size).collect();
         layers.insert(0, input_cols);
         layers.push(output_cols);
 
-        // copy input and output values.
-        let input = Matrix::from(rows, input_cols, input_cols + output_cols, data);
-        let output = Matrix::from(
-            rows,
-            output_cols,
-            input_cols + output_cols,
-            &data[input_cols..],
-        );
+        // hidden layers without an explicit activation and the output layer use `A`
+        let mut activations = Vec::with_capacity(hidden_layers.len() + 1);
+        for (i, l) in hidden_layers.iter().enumerate() {
+            if l.activation.is_none() && !A::ELEMENT_WISE {
+                return Err(NNError::InvalidLayerSpec(format!(
+                    "hidden layer {} needs an activation, `{}` only fits the output layer",
+                    i,
+                    std::any::type_name::<A>()
+                )));
+            }
+            activations.push(l.activation);
+        }
+        activations.push(None);
 
         // create model
-        let mut model = Tensor::from(&layers);
-        model.randomize();
+        let pieces: Vec<usize> = activations.iter().map(|&a| pieces::<A>(a)).collect();
+        let mut model = Tensor::try_from_pieces_in(&layers, &pieces)?;
+        model.initialize_with(initializer, &mut rng);
 
         // create gradient
-        let mut gradient = Tensor::from(&layers);
-        gradient.fill(0.0);
+        let mut gradient = Tensor::try_from_pieces_in(&layers, &pieces)?;
+        gradient.fill(T::zero());
 
 
         // return Architecture for neural network
-        Self {
+        Ok(Self {
             activation: std::marker::PhantomData,
+            loss: std::marker::PhantomData,
+            activations,
             model,
             gradient,
-            input,
-            output,
+            data,
+            rng,
+        })
+    }
+
+    /// Saves the layer sizes, activations, weights and biases, see `format` for the layout.
+    /// The dataset and the loss are not saved.
+    /// Fails with `NNError::Format` if `A` is a custom activation, which has no name.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let activations = self.get_named_activations().map_err(NNError::Format)?;
+        let mut encoder = Encoder::new(format::KIND_ARCH);
+        encoder.put_usize(activations.len())?;
+        for activation in &activations {
+            encoder.put_str(&activation.to_string())?;
+        }
+        self.model.encode(&mut encoder)?;
+        encoder.write(path)
+    }
+
+    /// Loads a network written by `Arch::save`, the output activation has to be `A`.
+    /// The loaded network has no dataset, see `Arch::set_dataset`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let bytes = fs::read(path)?;
+        let mut decoder = Decoder::new(&bytes, format::KIND_ARCH)?;
+
+        let count = decoder.get_usize()?;
+        let mut activations = Vec::with_capacity(count.min(1024));
+        for _ in 0..count {
+            activations.push(decoder.get_str()?.parse::<Activation>().map_err(format::invalid)?);
+        }
+        let model = Tensor::decode(&mut decoder)?;
+        decoder.finish()?;
+
+        Self::from_model(activations, model).map_err(format::invalid)
+    }
+
+    /// Builds a network without a dataset from stored activations and parameters.
+    pub(crate) fn from_model(
+        activations: Vec<Activation>,
+        model: Tensor<T>,
+    ) -> std::result::Result<Self, String> {
+        if A::ACTIVATION.is_none() || activations.last() != A::ACTIVATION.as_ref() {
+            return Err(format!(
+                "output activation is {:?}, expected {:?}",
+                activations.last(),
+                A::ACTIVATION
+            ));
+        }
+        if model.count != activations.len() {
+            return Err(format!(
+                "{} activations for {} layers",
+                activations.len(),
+                model.count
+            ));
+        }
+        for (i, activation) in activations.iter().enumerate() {
+            if model.zl[i].get_col_count() != model.al[i + 1].get_col_count() * activation.pieces() {
+                return Err(format!("layer {} does not fit activation {}", i, activation));
+            }
+        }
+
+        // the output layer uses `A`
+        let mut activations: Vec<Option<Activation>> = activations.into_iter().map(Some).collect();
+        if let Some(output) = activations.last_mut() {
+            *output = None;
         }
+
+        let input_cols = model.get_input().get_col_count();
+        let output_cols = model.get_output().get_col_count();
+        Ok(Self {
+            activation: std::marker::PhantomData,
+            loss: std::marker::PhantomData,
+            activations,
+            gradient: model.zero_like(),
+            model,
+            data: Dataset::new(Matrix::zero_in(0, input_cols), Matrix::zero_in(0, output_cols)),
+            rng: StdRng::from_entropy(),
+        })
     }
 
+    /// Reseeds the generator used by later random operations, e.g. the shuffling
+    /// during training of a loaded network. The current weights are kept.
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Replaces the dataset, `data` is laid out like in `Arch::new`.
+    pub fn set_data(&mut self, data: &[T], rows: usize) {
+        let input_cols = self.model.get_input().get_col_count();
+        let output_cols = self.model.get_output().get_col_count();
+        let stride = input_cols + output_cols;
+        assert!(
+            data.len() >= rows * stride,
+            "ERROR: Size of data is not equal to given rows and cols."
+        );
+        self.data = Dataset::from_interleaved(data, rows, input_cols, output_cols);
+    }
+
+    /// Replaces the dataset used by `train`, `cost` and `backprop`.
+    pub fn set_dataset(&mut self, dataset: Dataset<T>) {
+        self.try_set_dataset(dataset).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `set_dataset`, but returns an error if the cols of `dataset` don't fit the network.
+    pub fn try_set_dataset(&mut self, dataset: Dataset<T>) -> Result<()> {
+        self.check_dataset(&dataset)?;
+        self.data = dataset;
+        Ok(())
+    }
+
+    pub fn get_dataset(&self) -> &Dataset<T> {
+        &self.data
+    }
+
+    /// Errors if the cols of `dataset` don't fit the input and output layers.
+    fn check_dataset(&self, dataset: &Dataset<T>) -> Result<()> {
+        let input_cols = self.model.get_input().get_col_count();
+        let output_cols = self.model.get_output().get_col_count();
+        if dataset.get_input_col_count() != input_cols || dataset.get_target_col_count() != output_cols {
+            return Err(NNError::ShapeMismatch {
+                op: "dataset",
+                left: (dataset.get_input_col_count(), dataset.get_target_col_count()),
+                right: (input_cols, output_cols),
+            });
+        }
+        Ok(())
+    }
+
+    /// Runs `f` with the dataset of the network, which is moved out for the call
+    /// so that `f` can mutate the rest of the network.
+    fn with_own_data<R>(&mut self, f: impl FnOnce(&mut Self, &Dataset<T>) -> R) -> R {
+        let data = std::mem::take(&mut self.data);
+        let result = f(self, &data);
+        self.data = data;
+        result
+    }
+
+    /// Trains with `TrainConfig::default()`.
     pub fn train(&mut self) {
+        self.train_with(&TrainConfig::default());
+    }
+
+    /// Trains the model with the given hyperparameters using plain gradient descent
+    /// and returns the final cost.
+    pub fn train_with(&mut self, config: &TrainConfig) -> T {
+        self.train_with_optimizer(config, &mut Sgd)
+    }
+
+    /// Like `train_with` but every step is applied by `optimizer`, e.g. `Adam`.
+    /// The optimizer keeps its state, so passing the same one again resumes training.
+    pub fn train_with_optimizer(&mut self, config: &TrainConfig, optimizer: &mut dyn Optimizer<T>) -> T {
+        self.try_train_with_optimizer(config, optimizer).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `train_with_optimizer`, but returns an error for an empty dataset or an invalid `config`.
+    pub fn try_train_with_optimizer(&mut self, config: &TrainConfig, optimizer: &mut dyn Optimizer<T>) -> Result<T> {
+        self.with_own_data(|arch, data| arch.fit(data, config, optimizer))
+    }
+
+    /// Like `train_with` but on `dataset` instead of the dataset of the network.
+    pub fn train_on(&mut self, dataset: &Dataset<T>, config: &TrainConfig) -> T {
+        self.train_on_with_optimizer(dataset, config, &mut Sgd)
+    }
+
+    /// Like `train_with_optimizer` but on `dataset` instead of the dataset of the network.
+    pub fn train_on_with_optimizer(
+        &mut self,
+        dataset: &Dataset<T>,
+        config: &TrainConfig,
+        optimizer: &mut dyn Optimizer<T>,
+    ) -> T {
+        self.try_train_on_with_optimizer(dataset, config, optimizer)
+            .unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `train_on_with_optimizer`, but returns an error if `dataset` is empty
+    /// or doesn't fit the network, or for an invalid `config`.
+    pub fn try_train_on_with_optimizer(
+        &mut self,
+        dataset: &Dataset<T>,
+        config: &TrainConfig,
+        optimizer: &mut dyn Optimizer<T>,
+    ) -> Result<T> {
+        self.check_dataset(dataset)?;
+        self.fit(dataset, config, optimizer)
+    }
+
+    fn fit(&mut self, data: &Dataset<T>, config: &TrainConfig, optimizer: &mut dyn Optimizer<T>) -> Result<T> {
+        config.validate()?;
+        if data.is_empty() {
+            return Err(NNError::EmptyDataset);
+        }
+        let mut rows: Vec<usize> = (0..data.get_row_count()).collect();
+        let batch_size = config.batch_size.unwrap_or(rows.len()).clamp(1, rows.len());
+        // a seed in the config takes precedence over the generator of the network
+        let mut config_rng = config.seed.map(StdRng::seed_from_u64);
+        let (rate, eps) = (T::from_f64(config.rate), T::from_f64(config.eps));
+
+        let mut cost = self.cost_of(data);
+        if config.logs() {
+            println!("Initial cost = {}", cost);
+        }
 
-        // TODO: change default values and make them variable
-        let eps = 1e-1;
-        let rate = 1e-2;
-        let iterations = 200 * 1000;
+        for epoch in 1..=config.epochs {
+            if config.shuffle {
+                match config_rng.as_mut() {
+                    Some(rng) => rows.shuffle(rng),
+                    None => rows.shuffle(&mut self.rng),
+                }
+            }
+            for batch in rows.chunks(batch_size) {
+                match config.gradient_method {
+                    GradientMethod::Backprop => self.backprop_rows(data, batch),
+                    GradientMethod::FiniteDiff => self.finite_diff_rows(data, batch, eps),
+                }
+                optimizer.step(&mut self.model, &self.gradient, rate);
+            }
 
-        println!("Initial cost = {}", self.cost());
-        
-        for _ in 0..iterations {
-            self.finite_diff(eps);
-            self.learn(rate);
+            if config.needs_cost(epoch) {
+                let previous = cost;
+                cost = self.cost_of(data);
+                if config.should_log(epoch) {
+                    println!("Epoch {:>8}: cost = {}", epoch, cost);
+                }
+                if config.should_stop(previous.as_f64(), cost.as_f64()) {
+                    if config.logs() {
+                        println!("Stopped after {} epochs", epoch);
+                    }
+                    break;
+                }
+            }
         }
 
-        println!("Final cost   = {}", self.cost());
+        cost = self.cost_of(data);
+        if config.logs() {
+            println!("Final cost   = {}", cost);
+        }
+        Ok(cost)
     }
 
     pub fn _check_model(&mut self) {
         println!("Checking output");
-        for i in 0..self.input.get_row_count() {
-                self.model.get_input_mut().copy_from_slice(self.input.get_row_ref(i));
+        for i in 0..self.data.get_row_count() {
+                self.model.get_input_mut().copy_from_slice(self.data.get_inputs().get_row_ref(i));
                 self.feed_forward();
                 println!("{:?} : {:?}", self.model.get_input().get_data_ref(), self.model.get_output().get_data_ref());
         }
     }
 
+    /// Output of the network for one input row, see `predict_batch`.
+    pub fn predict(&self, input: &[T]) -> Vec<T> {
+        self.try_predict(input).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `predict`, but returns an error if `input` doesn't have one value per input unit.
+    pub fn try_predict(&self, input: &[T]) -> Result<Vec<T>> {
+        let input = MatrixView::new(input, 0, 1, input.len(), input.len());
+        Ok(self.try_predict_batch(input)?.get_row_ref(0).to_vec())
+    }
+
+    /// Output of the network for every row of `inputs`, one matrix product per layer.
+    ///
+    /// Unlike `feed_forward` this leaves the model untouched, so a trained network
+    /// can predict from several threads at once.
+    pub fn predict_batch<'a>(&self, inputs: impl Into<MatrixView<'a, T>>) -> Matrix<T> {
+        self.try_predict_batch(inputs).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `predict_batch`, but returns an error if the cols of `inputs` don't match the input layer.
+    pub fn try_predict_batch<'a>(&self, inputs: impl Into<MatrixView<'a, T>>) -> Result<Matrix<T>> {
+        let inputs = inputs.into();
+        let input_cols = self.model.get_input().get_col_count();
+        if inputs.get_col_count() != input_cols {
+            return Err(NNError::ShapeMismatch {
+                op: "predict",
+                left: (inputs.get_row_count(), inputs.get_col_count()),
+                right: (1, input_cols),
+            });
+        }
+        Ok(forward_batch::<A, T>(&self.activations, &self.model, inputs))
+    }
+
+    /// Output of the network for every input row of `dataset`, see `predict_batch`.
+    pub fn predict_dataset(&self, dataset: &Dataset<T>) -> Matrix<T> {
+        self.predict_batch(dataset.get_inputs())
+    }
+
+    /// Loss `L` averaged over every row of `dataset`, like `cost` but without
+    /// touching the network, e.g. to evaluate on a test set.
+    pub fn evaluate(&self, dataset: &Dataset<T>) -> T {
+        self.try_evaluate(dataset).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `evaluate`, but returns an error for an empty dataset or cols that don't fit.
+    pub fn try_evaluate(&self, dataset: &Dataset<T>) -> Result<T> {
+        self.check_dataset(dataset)?;
+        if dataset.is_empty() {
+            return Err(NNError::EmptyDataset);
+        }
+        let outputs = self.predict_dataset(dataset);
+        let targets = dataset.get_targets();
+        let mut c = T::zero();
+        for row in 0..dataset.get_row_count() {
+            c += L::loss_in(outputs.get_row_ref(row), targets.get_row_ref(row));
+        }
+        Ok(c / T::from_f64(dataset.get_row_count() as f64))
+    }
+
     pub fn feed_forward(&mut self) {
-        for i in 0..self.model.count {
-            // let mut next_al_layer = self.model.get_ref_al_mut(i+ 1);
-            let (left, right) = self.model.al.split_at_mut(i + 1);
-            let current_al_layer = left.get(left.len() - 1);
-            let next_al_layer = right.get_mut(0);
-            if current_al_layer.is_some() && next_al_layer.is_some() {
-                let current_al_layer = current_al_layer.unwrap();
-                let next_al_layer = next_al_layer.unwrap();
-
-                let current_wl_layer = &self.model.wl[i];// .get_ref_wl(i);
-                let current_bl_layer = &self.model.bl[i];
-
-                next_al_layer.dot(current_al_layer, current_wl_layer);
-                next_al_layer.add(current_bl_layer);
-                next_al_layer.activate::<Sigmoid>();
+        forward::<A, T>(&self.activations, &mut self.model);
+    }
+
+    /// Loss `L` averaged over every row of the dataset.
+    pub fn cost(&mut self) -> T {
+        self.try_cost().unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `cost`, but returns an error if the network has no dataset, e.g. after `Arch::load`.
+    pub fn try_cost(&mut self) -> Result<T> {
+        if self.data.is_empty() {
+            return Err(NNError::EmptyDataset);
+        }
+        Ok(self.with_own_data(|arch, data| arch.cost_of(data)))
+    }
+
+    fn cost_of(&mut self, data: &Dataset<T>) -> T {
+        let rows: Vec<usize> = (0..data.get_row_count()).collect();
+        self.cost_rows(data, &rows)
+    }
+
+    /// Cost over the given rows of `data`.
+    fn cost_rows(&mut self, data: &Dataset<T>, rows: &[usize]) -> T {
+        let n = rows.len();
+        let (inputs, targets) = (data.get_inputs(), data.get_targets());
+
+        #[cfg(feature = "parallel")]
+        if let Some(chunk) = parallel_chunk(n) {
+            use rayon::prelude::*;
+
+            let (activations, model) = (&self.activations, &self.model);
+            let partials: Vec<T> = rows
+                .par_chunks(chunk)
+                .map(|rows| {
+                    let mut model = model.clone();
+                    rows.iter()
+                        .map(|&i| sample_loss::<A, L, T>(activations, &mut model, inputs.get_row_ref(i), targets.get_row_ref(i)))
+                        .sum::<T>()
+                })
+                .collect();
+            return partials.iter().sum::<T>() / T::from_f64(n as f64);
+        }
+
+        let mut c = T::zero();
+        for &i in rows {
+            c += sample_loss::<A, L, T>(&self.activations, &mut self.model, inputs.get_row_ref(i), targets.get_row_ref(i));
+        }
+        c / T::from_f64(n as f64)
+    }
+
+    /// Computes the exact gradient of the cost into `self.gradient`.
+    ///
+    /// For every sample we do one forward pass and then walk the layers backwards,
+    /// `gradient.al` and `gradient.zl` are reused to hold the partial derivative of the cost
+    /// with respect to each activation and each weighted sum.
+    pub fn backprop(&mut self) {
+        self.try_backprop().unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `backprop`, but returns an error if the network has no dataset, e.g. after `Arch::load`.
+    pub fn try_backprop(&mut self) -> Result<()> {
+        if self.data.is_empty() {
+            return Err(NNError::EmptyDataset);
+        }
+        self.with_own_data(|arch, data| {
+            let rows: Vec<usize> = (0..data.get_row_count()).collect();
+            arch.backprop_rows(data, &rows);
+        });
+        Ok(())
+    }
+
+    /// `backprop` restricted to the given rows of `data`, i.e. one mini-batch.
+    fn backprop_rows(&mut self, data: &Dataset<T>, rows: &[usize]) {
+        let n = rows.len();
+        let count = self.model.count;
+
+        for i in 0..count {
+            self.gradient.wl[i].fill(T::zero());
+            self.gradient.bl[i].fill(T::zero());
+        }
+
+        self.accumulate_gradient(data, rows);
+
+        // average over the samples
+        let n = T::from_f64(n as f64);
+        for i in 0..count {
+            for g in self.gradient.wl[i].get_data_ref_mut() {
+                *g /= n;
+            }
+            for g in self.gradient.bl[i].get_data_ref_mut() {
+                *g /= n;
             }
         }
     }
 
-    fn cost(&mut self) -> NNET {
-        let mut c = 0.0;
-        let n = self.input.get_row_count();
-        for i in 0..n {
-            let x = self.input.get_row_ref(i);
-            self.model.get_input_mut().copy_from_slice(x);
-            self.feed_forward();
-            let y = self.output.get_row_ref(i);
-            for (j, output) in y.iter().enumerate().take(self.output.get_col_count()) {
-            // for j in 0..q {
-                let d = self.model.get_output().get_ref(0, j) - output;
-                c = d * d;
+    /// Adds the gradient of every given row to `self.gradient`.
+    ///
+    /// With the `parallel` feature a large batch is split into one chunk per thread,
+    /// each chunk works on its own copy of the model and the partial gradients are summed.
+    fn accumulate_gradient(&mut self, data: &Dataset<T>, rows: &[usize]) {
+        let (inputs, targets) = (data.get_inputs(), data.get_targets());
+
+        #[cfg(feature = "parallel")]
+        if let Some(chunk) = parallel_chunk(rows.len()) {
+            use rayon::prelude::*;
+
+            let (activations, model) = (&self.activations, &self.model);
+            let partials: Vec<Tensor<T>> = rows
+                .par_chunks(chunk)
+                .map(|rows| {
+                    let mut model = model.clone();
+                    let mut gradient = model.zero_like();
+                    for &i in rows {
+                        let (x, y) = (inputs.get_row_ref(i), targets.get_row_ref(i));
+                        sample_backprop::<A, L, T>(activations, &mut model, &mut gradient, x, y);
+                    }
+                    gradient
+                })
+                .collect();
+            // summed in chunk order, so the result does not depend on the scheduling
+            for partial in &partials {
+                for i in 0..self.model.count {
+                    self.gradient.wl[i].add(&partial.wl[i]);
+                    self.gradient.bl[i].add(&partial.bl[i]);
+                }
             }
+            return;
         }
-        c / n as NNET
+
+        for &i in rows {
+            sample_backprop::<A, L, T>(
+                &self.activations,
+                &mut self.model,
+                &mut self.gradient,
+                inputs.get_row_ref(i),
+                targets.get_row_ref(i),
+            );
+        }
+    }
+
+    pub fn finite_diff(&mut self, eps: T) {
+        self.try_finite_diff(eps).unwrap_or_else(|err| panic!("ERROR: {}", err))
     }
 
-    fn finite_diff(&mut self, eps: NNET) {
-        let mut saved: NNET;
-        let c: NNET = self.cost();
+    /// Like `finite_diff`, but returns an error if the network has no dataset.
+    pub fn try_finite_diff(&mut self, eps: T) -> Result<()> {
+        if self.data.is_empty() {
+            return Err(NNError::EmptyDataset);
+        }
+        self.with_own_data(|arch, data| {
+            let rows: Vec<usize> = (0..data.get_row_count()).collect();
+            arch.finite_diff_rows(data, &rows, eps);
+        });
+        Ok(())
+    }
+
+    /// `finite_diff` restricted to the given rows of `data`, i.e. one mini-batch.
+    fn finite_diff_rows(&mut self, data: &Dataset<T>, rows: &[usize], eps: T) {
+        let mut saved: T;
+        let c: T = self.cost_rows(data, rows);
         // for all inputs
         for i in 0..self.model.count {
             // calculate for weights
@@ -135,7 +729,7 @@ impl<A: ActivationFunction> Arch<A> {
                     *self.model.wl[i].get_ref_mut(j, k) += eps;
 
                     // save the calculated values in gradient
-                    *self.gradient.wl[i].get_ref_mut(j, k) = (self.cost() - c) / eps; 
+                    *self.gradient.wl[i].get_ref_mut(j, k) = (self.cost_rows(data, rows) - c) / eps; 
 
                     // return to the saved value.
                     *self.model.wl[i].get_ref_mut(j, k) = saved;
@@ -152,7 +746,7 @@ impl<A: ActivationFunction> Arch<A> {
                     *self.model.bl[i].get_ref_mut(j, k) += eps;
 
                     // save the calculated values in gradient
-                    *self.gradient.bl[i].get_ref_mut(j, k) = (self.cost() - c) / eps; 
+                    *self.gradient.bl[i].get_ref_mut(j, k) = (self.cost_rows(data, rows) - c) / eps; 
 
                     // return to the saved value.
                     *self.model.bl[i].get_ref_mut(j, k) = saved;
@@ -161,23 +755,25 @@ impl<A: ActivationFunction> Arch<A> {
         }
     }
 
-    fn learn(&mut self, rate: NNET) {
-        for i in 0..self.model.count {
+    /// Activation of each layer, `None` for the layers that use a custom `A`.
+    pub fn get_activations(&self) -> Vec<Option<Activation>> {
+        self.activations.iter().map(|a| a.or(A::ACTIVATION)).collect()
+    }
 
-            // learn for weights
-            for j in 0..self.model.wl[i].get_row_count() {
-                for k in 0..self.model.wl[i].get_col_count() {
-                    *self.model.wl[i].get_ref_mut(j, k) -= rate * self.gradient.wl[i].get_ref(j, k);
-                }
-            }
+    /// Like `get_activations`, but fails if a layer uses a custom `A`, which has no name.
+    pub(crate) fn get_named_activations(&self) -> std::result::Result<Vec<Activation>, String> {
+        self.get_activations()
+            .into_iter()
+            .map(|a| a.ok_or_else(|| format!("`{}` is a custom activation without a name", std::any::type_name::<A>())))
+            .collect()
+    }
 
-            // learn for biases
-            for j in 0..self.model.bl[i].get_row_count() {
-                for k in 0..self.model.bl[i].get_col_count() {
-                    *self.model.bl[i].get_ref_mut(j, k) -= rate * self.gradient.bl[i].get_ref(j, k);
-                }
-            }
-        }
+    pub fn get_model(&self) -> &Tensor<T> {
+        &self.model
+    }
+
+    pub fn get_gradient(&self) -> &Tensor<T> {
+        &self.gradient
     }
 
     pub fn print_gradient(&self) {
@@ -188,11 +784,164 @@ impl<A: ActivationFunction> Arch<A> {
         println!("Model: {}", self.model);
     }
 
+    /// Input columns of the dataset.
+    pub fn get_input(&self) -> MatrixView<'_, T> {
+        self.data.get_inputs().view()
+    }
+
+    /// Expected output columns of the dataset.
+    pub fn get_output(&self) -> MatrixView<'_, T> {
+        self.data.get_targets().view()
+    }
+
     pub fn print_given_input(&self) {
-        println!("Input: {}", self.input);
+        println!("Input: {}", self.get_input());
     }
 
     pub fn print_given_output(&self) {
-        println!("Output: {}", self.output);
+        println!("Output: {}", self.get_output());
+    }
+}
+
+/// Forward pass of the row in `model.al[0]` through every layer.
+fn forward<A: VectorActivation, T: Float>(activations: &[Option<Activation>], model: &mut Tensor<T>) {
+    for (i, activation) in activations.iter().enumerate() {
+        let (left, right) = model.al.split_at_mut(i + 1);
+        if let (Some(current_al_layer), Some(next_al_layer)) = (left.last(), right.first_mut()) {
+            let current_wl_layer = &model.wl[i];
+            let current_bl_layer = &model.bl[i];
+            let current_zl_layer = &mut model.zl[i];
+
+            // keep the weighted sum, backprop needs it for the derivative.
+            current_zl_layer.dot(current_al_layer, current_wl_layer);
+            current_zl_layer.add(current_bl_layer);
+            activate_row::<A, T>(
+                *activation,
+                current_zl_layer.get_row_ref(0),
+                next_al_layer.get_row_ref_mut(0),
+            );
+        }
     }
 }
+
+/// Forward pass of every row of `inputs` without touching the buffers of `model`.
+fn forward_batch<A: VectorActivation, T: Float>(
+    activations: &[Option<Activation>],
+    model: &Tensor<T>,
+    inputs: MatrixView<'_, T>,
+) -> Matrix<T> {
+    let rows = inputs.get_row_count();
+    let mut current = inputs.to_matrix();
+    for (i, activation) in activations.iter().enumerate() {
+        let mut z = Matrix::zero_in(rows, model.wl[i].get_col_count());
+        z.dot(&current, &model.wl[i]);
+        let bias = model.bl[i].get_row_ref(0);
+        let mut next = Matrix::zero_in(rows, model.al[i + 1].get_col_count());
+        for row in 0..rows {
+            for (z, b) in z.get_row_ref_mut(row).iter_mut().zip(bias) {
+                *z += *b;
+            }
+            activate_row::<A, T>(*activation, z.get_row_ref(row), next.get_row_ref_mut(row));
+        }
+        current = next;
+    }
+    current
+}
+
+/// Loss of one dataset row with input `x` and expected output `y`.
+fn sample_loss<A: VectorActivation, L: Loss, T: Float>(
+    activations: &[Option<Activation>],
+    model: &mut Tensor<T>,
+    x: &[T],
+    y: &[T],
+) -> T {
+    model.get_input_mut().copy_from_slice(x);
+    forward::<A, T>(activations, model);
+    L::loss_in(model.get_output().get_row_ref(0), y)
+}
+
+/// Adds the gradient of the loss of one dataset row with input `x` and expected output `y` to `gradient.wl` and `gradient.bl`.
+/// `gradient.al` and `gradient.zl` are overwritten, see `Arch::backprop`.
+fn sample_backprop<A: VectorActivation, L: Loss, T: Float>(
+    activations: &[Option<Activation>],
+    model: &mut Tensor<T>,
+    gradient: &mut Tensor<T>,
+    x: &[T],
+    y: &[T],
+) {
+    model.get_input_mut().copy_from_slice(x);
+    forward::<A, T>(activations, model);
+
+    // derivative of the cost with respect to the weighted sums of the output layer if the loss
+    // simplifies with its activation, otherwise with respect to the output layer
+    let last = model.count - 1;
+    let fused = activations[last].or(A::ACTIVATION).is_some_and(|activation| {
+        L::fused_derivative_in(
+            activation,
+            model.get_output().get_row_ref(0),
+            y,
+            gradient.zl[last].get_row_ref_mut(0),
+        )
+    });
+    if !fused {
+        L::derivative_in(
+            model.get_output().get_row_ref(0),
+            y,
+            gradient.get_output_mut().get_row_ref_mut(0),
+        );
+    }
+
+    for l in (0..model.count).rev() {
+        if !(fused && l == last) {
+            backward_row::<A, T>(
+                activations[l],
+                model.zl[l].get_row_ref(0),
+                model.al[l + 1].get_row_ref(0),
+                gradient.al[l + 1].get_row_ref(0),
+                gradient.zl[l].get_row_ref_mut(0),
+            );
+        }
+        gradient.al[l].fill(T::zero());
+        for j in 0..model.zl[l].get_col_count() {
+            let dz = *gradient.zl[l].get_ref(0, j);
+            *gradient.bl[l].get_ref_mut(0, j) += dz;
+            for k in 0..model.al[l].get_col_count() {
+                let a = *model.al[l].get_ref(0, k);
+                let w = *model.wl[l].get_ref(k, j);
+                *gradient.wl[l].get_ref_mut(k, j) += dz * a;
+                *gradient.al[l].get_ref_mut(0, k) += dz * w;
+            }
+        }
+    }
+}
+
+/// Applies the activation of one layer, `None` is `A`.
+fn activate_row<A: VectorActivation, T: Float>(activation: Option<Activation>, z: &[T], a: &mut [T]) {
+    match activation {
+        Some(activation) => activation.activate_row_in(z, a),
+        None => A::activate_row_in(z, a),
+    }
+}
+
+/// Like `activate_row`, for `VectorActivation::backward_row`.
+fn backward_row<A: VectorActivation, T: Float>(activation: Option<Activation>, z: &[T], a: &[T], da: &[T], dz: &mut [T]) {
+    match activation {
+        Some(activation) => activation.backward_row_in(z, a, da, dz),
+        None => A::backward_row_in(z, a, da, dz),
+    }
+}
+
+/// Weighted sums per output of a layer, see `Activation::pieces`. A custom `A` has one.
+fn pieces<A: VectorActivation>(activation: Option<Activation>) -> usize {
+    activation.or(A::ACTIVATION).map_or(1, |a| a.pieces())
+}
+
+/// Rows per thread for a batch of `n` rows, `None` if the batch is too small to split.
+#[cfg(feature = "parallel")]
+fn parallel_chunk(n: usize) -> Option<usize> {
+    /// Fewer rows per thread don't pay for copying the model.
+    const MIN_ROWS_PER_THREAD: usize = 16;
+
+    let threads = rayon::current_num_threads().min(n / MIN_ROWS_PER_THREAD);
+    (threads > 1).then(|| n.div_ceil(threads))
+}
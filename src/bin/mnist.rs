@@ -0,0 +1,65 @@
+//! Trains an MLP on MNIST, e.g. `cargo run --release --bin mnist -- data/mnist`.
+//!
+//! The directory has to hold the four uncompressed files of
+//! <http://yann.lecun.com/exdb/mnist/>, `train-images-idx3-ubyte` and so on.
+
+use std::{env, path::Path, time::Instant};
+
+use feoho_nn::{
+    Activation, Adam, Arch, CategoricalCrossEntropy, Dataset, Initializer, Layer, Matrix, Result, Softmax,
+    TrainConfig,
+};
+
+const CLASSES: usize = 10;
+
+fn main() -> Result<()> {
+    let dir = env::args().nth(1).unwrap_or_else(|| "data/mnist".to_string());
+    let dir = Path::new(&dir);
+    let load = |prefix: &str| -> Result<Dataset<f32>> {
+        Dataset::from_idx(
+            dir.join(format!("{}-images-idx3-ubyte", prefix)),
+            dir.join(format!("{}-labels-idx1-ubyte", prefix)),
+            CLASSES,
+        )
+    };
+    let train = load("train")?;
+    let test = load("t10k")?;
+    println!("train: {} images, test: {} images", train.get_row_count(), test.get_row_count());
+
+    let mut arch: Arch<Softmax, CategoricalCrossEntropy, f32> = Arch::from_dataset_with_seed(
+        train,
+        &[Layer::with(128, Activation::ReLU)],
+        Initializer::HeUniform,
+        42,
+    );
+    let config = TrainConfig {
+        rate: 1e-3,
+        epochs: 5,
+        batch_size: Some(64),
+        log_interval: Some(1),
+        ..TrainConfig::default()
+    };
+
+    let start = Instant::now();
+    arch.train_with_optimizer(&config, &mut Adam::default());
+    println!("trained in {:.1?}", start.elapsed());
+
+    println!("test loss:     {}", arch.evaluate(&test));
+    println!("test accuracy: {:.2}%", 100.0 * accuracy(&arch.predict_dataset(&test), test.get_targets()));
+    Ok(())
+}
+
+/// Share of rows whose largest output is the one-hot target.
+fn accuracy(outputs: &Matrix<f32>, targets: &Matrix<f32>) -> f64 {
+    let argmax = |row: &[f32]| {
+        row.iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(i, _)| i)
+    };
+    let rows = outputs.get_row_count();
+    let correct = (0..rows)
+        .filter(|&row| argmax(outputs.get_row_ref(row)) == argmax(targets.get_row_ref(row)))
+        .count();
+    correct as f64 / rows as f64
+}
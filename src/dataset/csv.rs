@@ -0,0 +1,255 @@
+//! Reading a `Dataset` from a csv file, see `Dataset::from_csv`.
+//!
+//! Fields are separated by `CsvOptions::delimiter` and may be quoted with `"`,
+//! a quote inside a quoted field is written as `""`. Quoted fields can't span lines.
+//! An empty field, `NA` or `?` is a missing value, see `MissingValues`.
+
+use std::{collections::BTreeSet, fs, path::Path};
+
+use crate::{Dataset, Float, NNError, Result};
+
+/// A col of a csv file, by position or by its name in the header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+    Index(usize),
+    Name(String),
+}
+
+impl From<usize> for Column {
+    fn from(index: usize) -> Self {
+        Self::Index(index)
+    }
+}
+
+impl From<&str> for Column {
+    fn from(name: &str) -> Self {
+        Self::Name(name.to_string())
+    }
+}
+
+impl From<String> for Column {
+    fn from(name: String) -> Self {
+        Self::Name(name)
+    }
+}
+
+/// What `Dataset::from_csv` does with a missing value in a selected col.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum MissingValues {
+    /// Fail with `NNError::Format` naming the line and the col.
+    #[default]
+    Error,
+    /// Skip the whole row.
+    Drop,
+    /// Use this value instead.
+    Fill(f64),
+}
+
+/// Options for `Dataset::from_csv`.
+///
+/// Start from `CsvOptions::default()` and override what you need:
+/// ```
+/// use feoho_nn::{Column, CsvOptions};
+/// let options = CsvOptions {
+///     targets: vec![Column::from("label")],
+///     ..CsvOptions::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvOptions {
+    /// Input cols in the order of the dataset, empty uses every col that is not a target.
+    pub inputs: Vec<Column>,
+
+    /// Target cols in the order of the dataset.
+    pub targets: Vec<Column>,
+
+    /// The first line after `skip_lines` holds the col names, which become
+    /// the names of the dataset. Needed to select cols by name.
+    pub has_header: bool,
+
+    /// Lines skipped before the header, e.g. comments.
+    pub skip_lines: usize,
+
+    pub delimiter: char,
+
+    pub missing: MissingValues,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            inputs: Vec::new(),
+            targets: Vec::new(),
+            has_header: true,
+            skip_lines: 0,
+            delimiter: ',',
+            missing: MissingValues::default(),
+        }
+    }
+}
+
+impl<T: Float> Dataset<T> {
+    /// Reads the cols selected by `options` from a csv file.
+    ///
+    /// A col with a value that is not a number is label encoded: its distinct values
+    /// are sorted and every value is replaced by its position, see `get_input_labels`.
+    pub fn from_csv(path: impl AsRef<Path>, options: &CsvOptions) -> Result<Self> {
+        Self::from_csv_str(&fs::read_to_string(path)?, options)
+    }
+
+    /// Like `from_csv`, but takes the content of the file.
+    pub fn from_csv_str(text: &str, options: &CsvOptions) -> Result<Self> {
+        let mut lines = text.lines().enumerate().skip(options.skip_lines);
+
+        let header = match options.has_header {
+            true => match lines.next() {
+                Some((_, line)) => Some(split_record(line, options.delimiter)?),
+                None => return Err(NNError::EmptyDataset),
+            },
+            false => None,
+        };
+
+        // every non blank line with its line number
+        let mut records = Vec::new();
+        for (i, line) in lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+            records.push((i + 1, split_record(line, options.delimiter)?));
+        }
+
+        let cols = match (&header, records.first()) {
+            (Some(header), _) => header.len(),
+            (None, Some((_, record))) => record.len(),
+            (None, None) => return Err(NNError::EmptyDataset),
+        };
+        if let Some((line, record)) = records.iter().find(|(_, record)| record.len() != cols) {
+            return Err(csv_error(format!("line {}: expected {} fields, found {}", line, cols, record.len())));
+        }
+
+        let targets = resolve(&options.targets, header.as_deref(), cols)?;
+        let inputs = match options.inputs.is_empty() {
+            true => (0..cols).filter(|col| !targets.contains(col)).collect(),
+            false => resolve(&options.inputs, header.as_deref(), cols)?,
+        };
+        let selected: Vec<usize> = inputs.iter().chain(&targets).copied().collect();
+        let col_name = |col: usize| match &header {
+            Some(header) => format!("`{}`", header[col]),
+            None => col.to_string(),
+        };
+
+        // missing values
+        let mut kept = Vec::with_capacity(records.len());
+        for (line, record) in &records {
+            match selected.iter().find(|&&col| is_missing(&record[col])) {
+                None => kept.push(record),
+                Some(&col) => match options.missing {
+                    MissingValues::Error => {
+                        return Err(csv_error(format!("line {}: missing value in column {}", line, col_name(col))))
+                    }
+                    MissingValues::Drop => {}
+                    MissingValues::Fill(_) => kept.push(record),
+                },
+            }
+        }
+        if kept.is_empty() {
+            return Err(NNError::EmptyDataset);
+        }
+
+        // label encoding of every col with a value that is not a number
+        let labels: Vec<Option<Vec<String>>> = selected
+            .iter()
+            .map(|&col| {
+                let values = kept.iter().map(|record| record[col].as_str()).filter(|value| !is_missing(value));
+                match values.clone().all(|value| value.parse::<f64>().is_ok()) {
+                    true => None,
+                    false => Some(values.map(str::to_string).collect::<BTreeSet<_>>().into_iter().collect()),
+                }
+            })
+            .collect();
+
+        let rows = kept.len();
+        let mut values: Vec<T> = Vec::with_capacity(rows * selected.len());
+        for record in &kept {
+            for (&col, labels) in selected.iter().zip(&labels) {
+                let field = record[col].as_str();
+                let value = match (labels, options.missing) {
+                    (_, MissingValues::Fill(fill)) if is_missing(field) => fill,
+                    (Some(labels), _) => labels.binary_search_by(|label| label.as_str().cmp(field)).unwrap_or_default() as f64,
+                    (None, _) => field.parse::<f64>().unwrap_or_default(),
+                };
+                values.push(T::from_f64(value));
+            }
+        }
+
+        let mut dataset = Self::try_from_interleaved(&values, rows, inputs.len(), targets.len())?;
+        if let Some(header) = &header {
+            dataset.input_names = Some(inputs.iter().map(|&col| header[col].clone()).collect());
+            dataset.target_names = Some(targets.iter().map(|&col| header[col].clone()).collect());
+        }
+        if labels.iter().any(Option::is_some) {
+            let mut labels = labels;
+            dataset.target_labels = labels.split_off(inputs.len());
+            dataset.input_labels = labels;
+        }
+        Ok(dataset)
+    }
+}
+
+fn csv_error(msg: String) -> NNError {
+    NNError::Format(format!("csv {}", msg))
+}
+
+fn is_missing(field: &str) -> bool {
+    field.is_empty() || field == "NA" || field == "?"
+}
+
+/// Index of every col in `columns`.
+fn resolve(columns: &[Column], header: Option<&[String]>, cols: usize) -> Result<Vec<usize>> {
+    columns
+        .iter()
+        .map(|column| match (column, header) {
+            (Column::Index(index), _) if *index < cols => Ok(*index),
+            (Column::Index(index), _) => Err(csv_error(format!(
+                "column {} is out of range for {} columns",
+                index, cols
+            ))),
+            (Column::Name(name), Some(header)) => header
+                .iter()
+                .position(|col| col == name)
+                .ok_or_else(|| csv_error(format!("no column named `{}`", name))),
+            (Column::Name(name), None) => Err(csv_error(format!(
+                "column `{}` is selected by name, but the file has no header",
+                name
+            ))),
+        })
+        .collect()
+}
+
+/// Splits one line into trimmed fields, see the module docs for quoting.
+fn split_record(line: &str, delimiter: char) -> Result<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(csv_error(format!("unterminated quote in `{}`", line)));
+    }
+    fields.push(field.trim().to_string());
+    Ok(fields)
+}
@@ -0,0 +1,109 @@
+//! Reading a `Dataset` from IDX files, the format of MNIST, see `Dataset::from_idx`.
+//!
+//! Every value is big-endian:
+//! ```text
+//! magic     4 bytes   0, 0, type, dims
+//! sizes     dims x u32
+//! data      product of sizes x type, in row major order
+//! ```
+//!
+//! Only the unsigned byte type `0x08` is supported, images have 3 dims
+//! `(count, rows, cols)` and labels 1 dim `(count)`.
+
+use std::{fs, path::Path};
+
+use crate::{Dataset, Float, Matrix, NNError, Result};
+
+const TYPE_U8: u8 = 0x08;
+
+impl<T: Float> Dataset<T> {
+    /// Reads an IDX image file and the matching label file, e.g. `train-images-idx3-ubyte`
+    /// and `train-labels-idx1-ubyte` of MNIST.
+    ///
+    /// Every image becomes one input row with its pixels scaled to `0..1`,
+    /// every label one target row with `classes` cols, one-hot encoded.
+    pub fn from_idx(images: impl AsRef<Path>, labels: impl AsRef<Path>, classes: usize) -> Result<Self> {
+        Self::from_idx_bytes(&fs::read(images)?, &fs::read(labels)?, classes)
+    }
+
+    /// Like `from_idx`, but takes the content of the files.
+    pub fn from_idx_bytes(images: &[u8], labels: &[u8], classes: usize) -> Result<Self> {
+        let (image_dims, pixels) = parse_idx(images, 3, "images")?;
+        let (label_dims, labels) = parse_idx(labels, 1, "labels")?;
+        let (count, rows, cols) = (image_dims[0], image_dims[1], image_dims[2]);
+        if label_dims[0] != count {
+            return Err(idx_error(format!("{} images but {} labels", count, label_dims[0])));
+        }
+        if count == 0 {
+            return Err(NNError::EmptyDataset);
+        }
+
+        let scale = T::from_f64(1.0 / 255.0);
+        let inputs: Vec<T> = pixels.iter().map(|&pixel| T::from_f64(pixel as f64) * scale).collect();
+        let len = count
+            .checked_mul(classes)
+            .ok_or_else(|| idx_error(format!("{} labels of {} classes are too large", count, classes)))?;
+        let mut targets = vec![T::zero(); len];
+        for (i, &label) in labels.iter().enumerate() {
+            if label as usize >= classes {
+                return Err(idx_error(format!("label {} of item {} is not below {} classes", label, i, classes)));
+            }
+            targets[i * classes + label as usize] = T::one();
+        }
+
+        let pixels = rows
+            .checked_mul(cols)
+            .ok_or_else(|| idx_error(format!("images of {}x{} pixels are too large", rows, cols)))?;
+        Self::try_new(
+            Matrix::try_from_in(count, pixels, pixels, &inputs)?,
+            Matrix::try_from_in(count, classes, classes, &targets)?,
+        )
+    }
+}
+
+fn idx_error(msg: String) -> NNError {
+    NNError::Format(format!("idx {}", msg))
+}
+
+/// Sizes and data of an IDX file of unsigned bytes with `dims` dims.
+fn parse_idx<'a>(bytes: &'a [u8], dims: usize, what: &str) -> Result<(Vec<usize>, &'a [u8])> {
+    let header = 4 + 4 * dims;
+    if bytes.len() < 4 {
+        return Err(idx_error(format!("{} file is too short for the magic number", what)));
+    }
+    if bytes[0] != 0 || bytes[1] != 0 {
+        return Err(idx_error(format!("{} file has the magic number {:02x?}", what, &bytes[..4])));
+    }
+    if bytes[2] != TYPE_U8 {
+        return Err(idx_error(format!(
+            "{} file has the type 0x{:02x}, only unsigned bytes (0x08) are supported",
+            what, bytes[2]
+        )));
+    }
+    if bytes[3] as usize != dims {
+        return Err(idx_error(format!("{} file has {} dims, expected {}", what, bytes[3], dims)));
+    }
+    if bytes.len() < header {
+        return Err(idx_error(format!("{} file is too short for {} sizes", what, dims)));
+    }
+
+    let sizes: Vec<usize> = bytes[4..header]
+        .chunks_exact(4)
+        .map(|size| u32::from_be_bytes([size[0], size[1], size[2], size[3]]) as usize)
+        .collect();
+    let len = sizes
+        .iter()
+        .try_fold(1usize, |len, &size| len.checked_mul(size))
+        .ok_or_else(|| idx_error(format!("{} file has the sizes {:?}, which are too large", what, sizes)))?;
+    let data = &bytes[header..];
+    if data.len() != len {
+        return Err(idx_error(format!(
+            "{} file has {} bytes of data, its sizes {:?} need {}",
+            what,
+            data.len(),
+            sizes,
+            len
+        )));
+    }
+    Ok((sizes, data))
+}
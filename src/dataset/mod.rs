@@ -0,0 +1,172 @@
+//! Samples to train, evaluate or run a network on, kept apart from `Arch`.
+
+mod csv;
+mod idx;
+
+pub use csv::{Column, CsvOptions, MissingValues};
+
+use crate::{Float, Matrix, MatrixView, NNError, Result, NNET};
+
+/// Rows of inputs with the expected output of each row, e.g. to train an `Arch`
+/// on one dataset and evaluate it on another.
+#[derive(Debug, Default, Clone)]
+pub struct Dataset<T = NNET> {
+    inputs: Matrix<T>,
+    targets: Matrix<T>,
+    /// Optional names of the input cols, e.g. the header of a csv file.
+    input_names: Option<Vec<String>>,
+    /// Optional names of the target cols.
+    target_names: Option<Vec<String>>,
+    /// Labels of label encoded input cols, value `i` stands for `labels[i]`.
+    /// Empty if no col is encoded.
+    input_labels: Vec<Option<Vec<String>>>,
+    /// Labels of label encoded target cols.
+    target_labels: Vec<Option<Vec<String>>>,
+}
+
+impl<T: Float> Dataset<T> {
+    pub fn new(inputs: Matrix<T>, targets: Matrix<T>) -> Self {
+        Self::try_new(inputs, targets).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `new`, but returns an error if `inputs` and `targets` have a different number of rows.
+    pub fn try_new(inputs: Matrix<T>, targets: Matrix<T>) -> Result<Self> {
+        if inputs.get_row_count() != targets.get_row_count() {
+            return Err(NNError::ShapeMismatch {
+                op: "Dataset::new",
+                left: (inputs.get_row_count(), inputs.get_col_count()),
+                right: (targets.get_row_count(), targets.get_col_count()),
+            });
+        }
+        Ok(Self {
+            inputs,
+            targets,
+            input_names: None,
+            target_names: None,
+            input_labels: Vec::new(),
+            target_labels: Vec::new(),
+        })
+    }
+
+    /// Splits `rows` rows that each hold `input_cols` inputs followed by `target_cols` targets,
+    /// the layout taken by `Arch::new`.
+    pub fn from_interleaved(data: &[T], rows: usize, input_cols: usize, target_cols: usize) -> Self {
+        Self::try_from_interleaved(data, rows, input_cols, target_cols).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `from_interleaved`, but returns an error if `data` is too short.
+    pub fn try_from_interleaved(data: &[T], rows: usize, input_cols: usize, target_cols: usize) -> Result<Self> {
+        let stride = input_cols.checked_add(target_cols).ok_or(NNError::ShapeMismatch {
+            op: "Dataset::from_interleaved",
+            left: (rows, input_cols),
+            right: (rows, target_cols),
+        })?;
+        let view = MatrixView::try_new(data, 0, rows, stride, stride)?;
+        Self::try_new(
+            view.slice_cols(0..input_cols).to_matrix(),
+            view.slice_cols(input_cols..stride).to_matrix(),
+        )
+    }
+
+    /// Takes the inputs and the targets as separate row-major arrays of `rows` rows each.
+    pub fn from_arrays(inputs: &[T], targets: &[T], rows: usize) -> Self {
+        Self::try_from_arrays(inputs, targets, rows).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `from_arrays`, but returns an error if a length is not a multiple of `rows`.
+    pub fn try_from_arrays(inputs: &[T], targets: &[T], rows: usize) -> Result<Self> {
+        if rows == 0 {
+            return Err(NNError::EmptyDataset);
+        }
+        if !inputs.len().is_multiple_of(rows) || !targets.len().is_multiple_of(rows) {
+            return Err(NNError::ShapeMismatch {
+                op: "Dataset::from_arrays",
+                left: (1, inputs.len()),
+                right: (1, targets.len()),
+            });
+        }
+        let (input_cols, target_cols) = (inputs.len() / rows, targets.len() / rows);
+        Self::try_new(
+            Matrix::try_from_in(rows, input_cols, input_cols, inputs)?,
+            Matrix::try_from_in(rows, target_cols, target_cols, targets)?,
+        )
+    }
+
+    /// Names every input and target col, e.g. for printing.
+    pub fn with_names<S: Into<String>>(self, input_names: Vec<S>, target_names: Vec<S>) -> Self {
+        self.try_with_names(input_names, target_names)
+            .unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `with_names`, but returns an error if the number of names and cols differ.
+    pub fn try_with_names<S: Into<String>>(mut self, input_names: Vec<S>, target_names: Vec<S>) -> Result<Self> {
+        if input_names.len() != self.get_input_col_count() || target_names.len() != self.get_target_col_count() {
+            return Err(NNError::ShapeMismatch {
+                op: "Dataset::with_names",
+                left: (1, self.get_input_col_count() + self.get_target_col_count()),
+                right: (1, input_names.len() + target_names.len()),
+            });
+        }
+        self.input_names = Some(input_names.into_iter().map(Into::into).collect());
+        self.target_names = Some(target_names.into_iter().map(Into::into).collect());
+        Ok(self)
+    }
+
+    pub fn get_row_count(&self) -> usize {
+        self.inputs.get_row_count()
+    }
+
+    pub fn get_input_col_count(&self) -> usize {
+        self.inputs.get_col_count()
+    }
+
+    pub fn get_target_col_count(&self) -> usize {
+        self.targets.get_col_count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.get_row_count() == 0
+    }
+
+    pub fn get_inputs(&self) -> &Matrix<T> {
+        &self.inputs
+    }
+
+    pub fn get_targets(&self) -> &Matrix<T> {
+        &self.targets
+    }
+
+    pub fn get_input_names(&self) -> Option<&[String]> {
+        self.input_names.as_deref()
+    }
+
+    pub fn get_target_names(&self) -> Option<&[String]> {
+        self.target_names.as_deref()
+    }
+
+    /// Labels of input col `col` if it was label encoded, see `Dataset::from_csv`.
+    pub fn get_input_labels(&self, col: usize) -> Option<&[String]> {
+        self.input_labels.get(col).and_then(Option::as_deref)
+    }
+
+    /// Labels of target col `col` if it was label encoded, see `Dataset::from_csv`.
+    pub fn get_target_labels(&self, col: usize) -> Option<&[String]> {
+        self.target_labels.get(col).and_then(Option::as_deref)
+    }
+
+    /// Splits into rows `0..mid` and `mid..rows`, e.g. for a train/test split.
+    /// Both halves keep the names and labels.
+    pub fn split_at_row(&self, mid: usize) -> (Self, Self) {
+        let (inputs_top, inputs_bottom) = self.inputs.split_at_row(mid);
+        let (targets_top, targets_bottom) = self.targets.split_at_row(mid);
+        let with_names = |inputs, targets| Self {
+            inputs,
+            targets,
+            input_names: self.input_names.clone(),
+            target_names: self.target_names.clone(),
+            input_labels: self.input_labels.clone(),
+            target_labels: self.target_labels.clone(),
+        };
+        (with_names(inputs_top, targets_top), with_names(inputs_bottom, targets_bottom))
+    }
+}
@@ -0,0 +1,64 @@
+use std::{error, fmt, io};
+
+/// Everything that can go wrong in this crate.
+#[derive(Debug)]
+pub enum NNError {
+    /// Two shapes, as `(rows, cols)`, that do not fit together in `op`.
+    /// For an operation on a flat slice the slice is reported as `(1, len)`.
+    ShapeMismatch {
+        op: &'static str,
+        left: (usize, usize),
+        right: (usize, usize),
+    },
+    /// `index` as `(row, col)` is outside of a matrix of `shape`.
+    IndexOutOfBounds {
+        index: (usize, usize),
+        shape: (usize, usize),
+    },
+    /// There are no rows to work with.
+    EmptyDataset,
+    /// A layer description that can't be turned into a network.
+    InvalidLayerSpec(String),
+    /// A `TrainConfig` with a value that can't be trained with.
+    InvalidConfig(String),
+    Io(io::Error),
+    /// A file or serialized value that is not in the expected format.
+    Format(String),
+}
+
+impl fmt::Display for NNError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ShapeMismatch { op, left, right } => write!(
+                f,
+                "shape mismatch in {}: {}x{} and {}x{}",
+                op, left.0, left.1, right.0, right.1
+            ),
+            Self::IndexOutOfBounds { index, shape } => write!(
+                f,
+                "index ({}, {}) is out of bounds for a {}x{} matrix",
+                index.0, index.1, shape.0, shape.1
+            ),
+            Self::EmptyDataset => write!(f, "dataset is empty"),
+            Self::InvalidLayerSpec(msg) => write!(f, "invalid layer spec: {}", msg),
+            Self::InvalidConfig(msg) => write!(f, "invalid train config: {}", msg),
+            Self::Io(err) => write!(f, "io error: {}", err),
+            Self::Format(msg) => write!(f, "invalid format: {}", msg),
+        }
+    }
+}
+
+impl error::Error for NNError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for NNError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
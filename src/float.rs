@@ -0,0 +1,71 @@
+//! Element type of `Matrix`, `Tensor` and `Arch`.
+
+use std::{
+    fmt::{Debug, Display},
+    iter::Sum,
+    ops::{AddAssign, DivAssign, MulAssign, SubAssign},
+};
+
+use rand::distributions::uniform::SampleUniform;
+
+/// Floating point type of the values of a network, implemented for `f32` and `f64`.
+///
+/// Every type defaults to `NNET`, i.e. `f64`, `f32` halves the memory of a model.
+pub trait Float:
+    num_traits::Float
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+    + Sum
+    + for<'a> Sum<&'a Self>
+    + Default
+    + Debug
+    + Display
+    + SampleUniform
+    + Send
+    + Sync
+    + 'static
+    + private::Sealed
+{
+    /// Converts a constant or a value computed in `f64`, rounding for `f32`.
+    fn from_f64(x: f64) -> Self;
+    /// Converts to `f64`, exact for both implementations.
+    fn as_f64(self) -> f64;
+}
+
+/// Copies a row into `f64`, for the default `_in` functions of traits implemented in `f64` only.
+pub(crate) fn to_f64_vec<T: Float>(row: &[T]) -> Vec<f64> {
+    row.iter().map(|x| x.as_f64()).collect()
+}
+
+/// Copies an `f64` row back into `dest`, see `to_f64_vec`.
+pub(crate) fn copy_from_f64<T: Float>(dest: &mut [T], row: &[f64]) {
+    for (d, x) in dest.iter_mut().zip(row) {
+        *d = T::from_f64(*x);
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+}
+
+impl Float for f32 {
+    fn from_f64(x: f64) -> Self {
+        x as f32
+    }
+    fn as_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Float for f64 {
+    fn from_f64(x: f64) -> Self {
+        x
+    }
+    fn as_f64(self) -> f64 {
+        self
+    }
+}
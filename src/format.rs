@@ -0,0 +1,197 @@
+//! Binary file format used by `Tensor::save` and `Arch::save`.
+//!
+//! Every value is little-endian, integers are `u32` and elements are `f64`,
+//! the elements of an `f32` model are widened when saved and rounded when loaded.
+//!
+//! ```text
+//! magic     4 bytes   b"FENN"
+//! version   u32       FORMAT_VERSION
+//! kind      u8        0 = Tensor, 1 = Arch
+//! payload   ...       see below
+//! checksum  u32       CRC-32 (IEEE) of every byte before it
+//! ```
+//!
+//! Tensor payload:
+//! ```text
+//! count     u32                   number of weight layers
+//! layers    (count + 1) x u32     units of every layer, input first
+//! wl, bl    count x (matrix, matrix)
+//! matrix    rows u32, cols u32, rows * cols x f64 in row major order
+//! ```
+//!
+//! Arch payload:
+//! ```text
+//! count     u32                   number of layers with an activation
+//! names     count x string        activation of every layer, e.g. `relu` or `maxout(2)`
+//! string    len u32, len bytes of UTF-8
+//! tensor    Tensor payload
+//! ```
+
+use std::{fs, path::Path};
+
+use crate::{Float, Matrix, NNError, Result, NNET};
+
+pub(crate) const MAGIC: &[u8; 4] = b"FENN";
+pub(crate) const FORMAT_VERSION: u32 = 1;
+
+pub(crate) const KIND_TENSOR: u8 = 0;
+pub(crate) const KIND_ARCH: u8 = 1;
+
+/// Error for a file that is not in the expected format.
+pub(crate) fn invalid(msg: String) -> NNError {
+    NNError::Format(msg)
+}
+
+/// CRC-32 with the IEEE polynomial, as used by zip and png.
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+pub(crate) struct Encoder {
+    buf: Vec<u8>,
+}
+
+impl Encoder {
+    pub(crate) fn new(kind: u8) -> Self {
+        let mut encoder = Self { buf: Vec::new() };
+        encoder.buf.extend_from_slice(MAGIC);
+        encoder.put_u32(FORMAT_VERSION);
+        encoder.buf.push(kind);
+        encoder
+    }
+
+    pub(crate) fn put_u32(&mut self, val: u32) {
+        self.buf.extend_from_slice(&val.to_le_bytes());
+    }
+
+    /// Fails if `val` does not fit in the `u32` of the format.
+    pub(crate) fn put_usize(&mut self, val: usize) -> Result<()> {
+        let val = u32::try_from(val).map_err(|_| invalid(format!("{} does not fit in a u32", val)))?;
+        self.put_u32(val);
+        Ok(())
+    }
+
+    pub(crate) fn put_str(&mut self, val: &str) -> Result<()> {
+        self.put_usize(val.len())?;
+        self.buf.extend_from_slice(val.as_bytes());
+        Ok(())
+    }
+
+    pub(crate) fn put_matrix<T: Float>(&mut self, matrix: &Matrix<T>) -> Result<()> {
+        self.put_usize(matrix.get_row_count())?;
+        self.put_usize(matrix.get_col_count())?;
+        for row in 0..matrix.get_row_count() {
+            for val in matrix.get_row_ref(row) {
+                self.buf.extend_from_slice(&val.as_f64().to_le_bytes());
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends the checksum and returns the finished file content.
+    pub(crate) fn finish(mut self) -> Vec<u8> {
+        let checksum = crc32(&self.buf);
+        self.put_u32(checksum);
+        self.buf
+    }
+
+    pub(crate) fn write(self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, self.finish())?;
+        Ok(())
+    }
+}
+
+pub(crate) struct Decoder<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    /// Checks magic, version, kind and checksum, the decoder starts at the payload.
+    pub(crate) fn new(bytes: &'a [u8], kind: u8) -> Result<Self> {
+        let header = MAGIC.len() + 4 + 1;
+        if bytes.len() < header + 4 {
+            return Err(invalid(format!("file is too short, {} bytes", bytes.len())));
+        }
+        let (content, checksum) = bytes.split_at(bytes.len() - 4);
+        let expected = u32::from_le_bytes(checksum.try_into().unwrap());
+        let actual = crc32(content);
+        if expected != actual {
+            return Err(invalid(format!(
+                "checksum mismatch, expected {:08x} found {:08x}",
+                expected, actual
+            )));
+        }
+
+        let mut decoder = Self { buf: content, pos: 0 };
+        if decoder.take(MAGIC.len())? != MAGIC {
+            return Err(invalid("missing FENN magic header".to_string()));
+        }
+        let version = decoder.get_u32()?;
+        if version != FORMAT_VERSION {
+            return Err(invalid(format!("unsupported format version {}", version)));
+        }
+        let found = decoder.take(1)?[0];
+        if found != kind {
+            return Err(invalid(format!("expected kind {} found {}", kind, found)));
+        }
+        Ok(decoder)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.buf.len() - self.pos < len {
+            return Err(invalid("unexpected end of file".to_string()));
+        }
+        let bytes = &self.buf[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    pub(crate) fn get_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn get_usize(&mut self) -> Result<usize> {
+        Ok(self.get_u32()? as usize)
+    }
+
+    pub(crate) fn get_str(&mut self) -> Result<&'a str> {
+        let len = self.get_usize()?;
+        std::str::from_utf8(self.take(len)?).map_err(|err| invalid(err.to_string()))
+    }
+
+    pub(crate) fn get_matrix<T: Float>(&mut self) -> Result<Matrix<T>> {
+        let rows = self.get_usize()?;
+        let cols = self.get_usize()?;
+        let size = std::mem::size_of::<NNET>();
+        let len = rows
+            .checked_mul(cols)
+            .and_then(|len| len.checked_mul(size))
+            .ok_or_else(|| invalid(format!("matrix of {}x{} is too large", rows, cols)))?;
+        let data: Vec<T> = self
+            .take(len)?
+            .chunks_exact(size)
+            .map(|bytes| T::from_f64(NNET::from_le_bytes(bytes.try_into().unwrap())))
+            .collect();
+        Ok(Matrix::from_in(rows, cols, cols, &data))
+    }
+
+    /// Fails if anything is left after the payload.
+    pub(crate) fn finish(self) -> Result<()> {
+        if self.pos != self.buf.len() {
+            return Err(invalid(format!(
+                "{} trailing bytes after the payload",
+                self.buf.len() - self.pos
+            )));
+        }
+        Ok(())
+    }
+}
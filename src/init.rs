@@ -0,0 +1,130 @@
+use rand::Rng;
+
+use crate::{Float, Matrix, NNET};
+
+/// How the weights of a layer are initialized, based on its fan-in (rows of `wl`)
+/// and fan-out (cols of `wl`). Every initializer but `Uniform` sets the biases to zero.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Initializer {
+    /// Uniform in `0.0..1.0`, weights and biases. Only suited for tiny networks.
+    Uniform,
+    /// Glorot, uniform in `±sqrt(6 / (fan_in + fan_out))`, for `Sigmoid` and `Tanh`.
+    #[default]
+    XavierUniform,
+    /// Glorot, normal with std `sqrt(2 / (fan_in + fan_out))`.
+    XavierNormal,
+    /// Kaiming, uniform in `±sqrt(6 / fan_in)`, for `ReLU` and its variants.
+    HeUniform,
+    /// Kaiming, normal with std `sqrt(2 / fan_in)`.
+    HeNormal,
+    /// Uniform in `±sqrt(3 / fan_in)`.
+    LeCunUniform,
+    /// Normal with std `sqrt(1 / fan_in)`.
+    LeCunNormal,
+    /// Orthonormal rows or cols, whichever there are fewer of.
+    Orthogonal,
+    /// Everything zero. The units of a layer stay identical during training,
+    /// so this is mostly useful for testing.
+    Zeros,
+}
+
+impl Initializer {
+    /// Fills the weights of one layer.
+    pub fn init_weights<T: Float, R: Rng + ?Sized>(&self, weights: &mut Matrix<T>, rng: &mut R) {
+        let fan_in = weights.get_row_count() as NNET;
+        let fan_out = weights.get_col_count() as NNET;
+        match self {
+            Self::Uniform => fill_uniform(weights, 0.0, 1.0, rng),
+            Self::XavierUniform => {
+                let limit = (6.0 / (fan_in + fan_out)).sqrt();
+                fill_uniform(weights, -limit, limit, rng);
+            }
+            Self::XavierNormal => fill_normal(weights, (2.0 / (fan_in + fan_out)).sqrt(), rng),
+            Self::HeUniform => {
+                let limit = (6.0 / fan_in).sqrt();
+                fill_uniform(weights, -limit, limit, rng);
+            }
+            Self::HeNormal => fill_normal(weights, (2.0 / fan_in).sqrt(), rng),
+            Self::LeCunUniform => {
+                let limit = (3.0 / fan_in).sqrt();
+                fill_uniform(weights, -limit, limit, rng);
+            }
+            Self::LeCunNormal => fill_normal(weights, (1.0 / fan_in).sqrt(), rng),
+            Self::Orthogonal => fill_orthogonal(weights, rng),
+            Self::Zeros => weights.fill(T::zero()),
+        }
+    }
+
+    /// Fills the biases of one layer.
+    pub fn init_biases<T: Float, R: Rng + ?Sized>(&self, biases: &mut Matrix<T>, rng: &mut R) {
+        match self {
+            Self::Uniform => fill_uniform(biases, 0.0, 1.0, rng),
+            _ => biases.fill(T::zero()),
+        }
+    }
+}
+
+// the values are drawn as `f64` for every element type, so a seed gives the same network
+fn fill_uniform<T: Float, R: Rng + ?Sized>(matrix: &mut Matrix<T>, low: NNET, high: NNET, rng: &mut R) {
+    if low == high {
+        matrix.fill(T::from_f64(low));
+        return;
+    }
+    for row in 0..matrix.get_row_count() {
+        for x in matrix.get_row_ref_mut(row) {
+            *x = T::from_f64(rng.gen_range(low..high));
+        }
+    }
+}
+
+/// Standard normal sample using the Box-Muller transform.
+pub(crate) fn standard_normal<R: Rng + ?Sized>(rng: &mut R) -> NNET {
+    // 1 - gen() is in (0, 1], so the logarithm stays finite
+    let u1: NNET = 1.0 - rng.gen::<NNET>();
+    let u2: NNET = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+fn fill_normal<T: Float, R: Rng + ?Sized>(matrix: &mut Matrix<T>, std: NNET, rng: &mut R) {
+    for row in 0..matrix.get_row_count() {
+        for x in matrix.get_row_ref_mut(row) {
+            *x = T::from_f64(std * standard_normal(rng));
+        }
+    }
+}
+
+/// Gram-Schmidt on a random normal matrix. For a tall matrix the cols are orthonormal,
+/// for a wide one the rows are.
+fn fill_orthogonal<T: Float, R: Rng + ?Sized>(matrix: &mut Matrix<T>, rng: &mut R) {
+    let rows = matrix.get_row_count();
+    let cols = matrix.get_col_count();
+    // orthonormalize `count` vectors of length `len`
+    let (count, len) = if rows >= cols { (cols, rows) } else { (rows, cols) };
+
+    let mut vectors: Vec<Vec<NNET>> = Vec::with_capacity(count);
+    while vectors.len() < count {
+        let mut v: Vec<NNET> = (0..len).map(|_| standard_normal(rng)).collect();
+        for u in &vectors {
+            let dot: NNET = v.iter().zip(u).map(|(v, u)| v * u).sum();
+            for (v, u) in v.iter_mut().zip(u) {
+                *v -= dot * u;
+            }
+        }
+        let norm = v.iter().map(|v| v * v).sum::<NNET>().sqrt();
+        // a nearly dependent sample is simply drawn again
+        if norm > 1e-6 {
+            v.iter_mut().for_each(|v| *v /= norm);
+            vectors.push(v);
+        }
+    }
+
+    for (i, v) in vectors.iter().enumerate() {
+        for (j, x) in v.iter().enumerate() {
+            if rows >= cols {
+                *matrix.get_ref_mut(j, i) = T::from_f64(*x);
+            } else {
+                *matrix.get_ref_mut(i, j) = T::from_f64(*x);
+            }
+        }
+    }
+}
@@ -1,11 +1,30 @@
 mod activation;
 mod arch;
+mod dataset;
+mod error;
+mod float;
+mod format;
+mod init;
+mod loss;
 mod matrix;
+mod optimizer;
+#[cfg(feature = "serde")]
+mod serialize;
 mod tensor;
+mod train;
 mod utils;
 
 pub use activation::*;
-pub use arch::Arch;
-pub use matrix::Matrix;
+pub use arch::{Arch, Layer};
+pub use dataset::{Column, CsvOptions, Dataset, MissingValues};
+pub use error::NNError;
+pub use float::Float;
+pub use loss::{
+    BinaryCrossEntropy, CategoricalCrossEntropy, Hinge, Huber, Loss, Mae, Mse, NegativeLogLikelihood,
+};
+pub use init::Initializer;
+pub use matrix::{Matrix, MatrixView, MatrixViewMut};
+pub use optimizer::{Adagrad, Adam, AdamW, Momentum, Nesterov, Optimizer, RmsProp, Sgd};
 pub use tensor::Tensor;
+pub use train::{GradientMethod, TrainConfig};
 pub use utils::{Result, NNET};
@@ -0,0 +1,208 @@
+use crate::{
+    float::{copy_from_f64, to_f64_vec},
+    Activation, Float, NNET,
+};
+
+/// Smallest probability fed into a logarithm, keeps the log losses finite.
+/// Only the losses are clamped, a clamped derivative would cut the gradient of a wrong output.
+const LOG_EPS: f64 = 1e-12;
+
+/// `LOG_EPS` in `T`, a normal number in `f32` as well.
+fn log_eps<T: Float>() -> T {
+    T::from_f64(LOG_EPS)
+}
+
+/// Loss of a single sample, `Arch` averages it over the rows of the dataset.
+///
+/// The `_in` functions work on any element type, `loss` and `derivative` are their `f64` versions.
+/// Like for `ActivationFunction`, implementing only the `f64` functions is enough.
+pub trait Loss {
+    /// Loss between the `output` of the network and the expected `target` row.
+    fn loss_in<T: Float>(output: &[T], target: &[T]) -> T {
+        T::from_f64(Self::loss(&to_f64_vec(output), &to_f64_vec(target)))
+    }
+    /// Partial derivative of `loss` with respect to each output, written into `grad`.
+    fn derivative_in<T: Float>(output: &[T], target: &[T], grad: &mut [T]) {
+        let mut out = vec![0.0; grad.len()];
+        Self::derivative(&to_f64_vec(output), &to_f64_vec(target), &mut out);
+        copy_from_f64(grad, &out);
+    }
+
+    /// Writes the derivative of `loss` with respect to the weighted sums `dz` of an output layer
+    /// with the given `activation` and returns true, if the two simplify together, e.g. to `y - t`
+    /// for `Softmax` and `CategoricalCrossEntropy`. The default returns false, `Arch` then goes
+    /// through `derivative_in` and the Jacobian of the activation, which loses the gradient once
+    /// a confidently wrong output rounds to zero.
+    fn fused_derivative_in<T: Float>(_activation: Activation, _output: &[T], _target: &[T], _dz: &mut [T]) -> bool {
+        false
+    }
+
+    fn loss(output: &[NNET], target: &[NNET]) -> NNET {
+        Self::loss_in(output, target)
+    }
+
+    fn derivative(output: &[NNET], target: &[NNET], grad: &mut [NNET]) {
+        Self::derivative_in(output, target, grad)
+    }
+}
+
+/// Squared error, summed over the output columns.
+pub struct Mse;
+impl Loss for Mse {
+    fn loss_in<T: Float>(output: &[T], target: &[T]) -> T {
+        output.iter().zip(target).map(|(&y, &t)| (y - t) * (y - t)).sum()
+    }
+    fn derivative_in<T: Float>(output: &[T], target: &[T], grad: &mut [T]) {
+        for ((g, y), t) in grad.iter_mut().zip(output).zip(target) {
+            *g = T::from_f64(2.0) * (*y - *t);
+        }
+    }
+}
+
+/// Absolute error, summed over the output columns.
+pub struct Mae;
+impl Loss for Mae {
+    fn loss_in<T: Float>(output: &[T], target: &[T]) -> T {
+        output.iter().zip(target).map(|(&y, &t)| (y - t).abs()).sum()
+    }
+    fn derivative_in<T: Float>(output: &[T], target: &[T], grad: &mut [T]) {
+        for ((g, y), t) in grad.iter_mut().zip(output).zip(target) {
+            *g = if y > t {
+                T::one()
+            } else if y < t {
+                -T::one()
+            } else {
+                T::zero()
+            };
+        }
+    }
+}
+
+/// Binary cross-entropy, expects outputs in (0, 1), e.g. from `Sigmoid`,
+/// and targets that are 0 or 1.
+pub struct BinaryCrossEntropy;
+impl Loss for BinaryCrossEntropy {
+    fn loss_in<T: Float>(output: &[T], target: &[T]) -> T {
+        output
+            .iter()
+            .zip(target)
+            .map(|(&y, &t)| {
+                // `1 - y` is clamped on its own, `1 - 1e-12` would round to one in `f32`
+                -(t * y.max(log_eps()).ln() + (T::one() - t) * (T::one() - y).max(log_eps()).ln())
+            })
+            .sum()
+    }
+    fn derivative_in<T: Float>(output: &[T], target: &[T], grad: &mut [T]) {
+        for ((g, y), t) in grad.iter_mut().zip(output).zip(target) {
+            // not clamped to `log_eps` like the loss, that would cut the gradient of a saturated
+            // wrong output, only an output of exactly zero or one is kept from dividing by zero
+            *g = (*y - *t) / (*y * (T::one() - *y)).max(T::min_positive_value());
+        }
+    }
+    fn fused_derivative_in<T: Float>(activation: Activation, output: &[T], target: &[T], dz: &mut [T]) -> bool {
+        if activation != Activation::Sigmoid {
+            return false;
+        }
+        for ((dz, y), t) in dz.iter_mut().zip(output).zip(target) {
+            *dz = *y - *t;
+        }
+        true
+    }
+}
+
+/// Categorical cross-entropy, expects the output row to be a probability
+/// distribution, i.e. a softmax output layer, and a one-hot target row.
+pub struct CategoricalCrossEntropy;
+impl Loss for CategoricalCrossEntropy {
+    fn loss_in<T: Float>(output: &[T], target: &[T]) -> T {
+        output
+            .iter()
+            .zip(target)
+            .map(|(&y, &t)| -t * y.max(log_eps()).ln())
+            .sum()
+    }
+    fn derivative_in<T: Float>(output: &[T], target: &[T], grad: &mut [T]) {
+        for ((g, y), t) in grad.iter_mut().zip(output).zip(target) {
+            // clamping to `log_eps` here would cut the gradient of a confidently wrong output,
+            // only an output of zero is kept from dividing by zero
+            *g = if *t == T::zero() {
+                T::zero()
+            } else {
+                -*t / y.max(T::min_positive_value())
+            };
+        }
+    }
+    fn fused_derivative_in<T: Float>(activation: Activation, output: &[T], target: &[T], dz: &mut [T]) -> bool {
+        if activation != Activation::Softmax {
+            return false;
+        }
+        // y * sum(t) - t, i.e. y - t for a one-hot target
+        let sum: T = target.iter().sum();
+        for ((dz, y), t) in dz.iter_mut().zip(output).zip(target) {
+            *dz = *y * sum - *t;
+        }
+        true
+    }
+}
+
+/// Negative log-likelihood, expects the output row to hold log-probabilities,
+/// i.e. a `LogSoftmax` output layer, and a one-hot target row.
+pub struct NegativeLogLikelihood;
+impl Loss for NegativeLogLikelihood {
+    fn loss_in<T: Float>(output: &[T], target: &[T]) -> T {
+        -output.iter().zip(target).map(|(&y, &t)| t * y).sum::<T>()
+    }
+    fn derivative_in<T: Float>(_output: &[T], target: &[T], grad: &mut [T]) {
+        for (g, t) in grad.iter_mut().zip(target) {
+            *g = -*t;
+        }
+    }
+}
+
+/// Huber loss, squared for errors smaller than `Huber::DELTA` and linear above,
+/// less sensitive to outliers than `Mse`.
+pub struct Huber;
+impl Huber {
+    pub const DELTA: f64 = 1.0;
+}
+impl Loss for Huber {
+    fn loss_in<T: Float>(output: &[T], target: &[T]) -> T {
+        output
+            .iter()
+            .zip(target)
+            .map(|(&y, &t)| {
+                let d = (y - t).abs();
+                let delta = T::from_f64(Self::DELTA);
+                let half = T::from_f64(0.5);
+                if d <= delta {
+                    half * d * d
+                } else {
+                    delta * (d - half * delta)
+                }
+            })
+            .sum()
+    }
+    fn derivative_in<T: Float>(output: &[T], target: &[T], grad: &mut [T]) {
+        for ((g, y), t) in grad.iter_mut().zip(output).zip(target) {
+            let delta = T::from_f64(Self::DELTA);
+            *g = (*y - *t).clamp(-delta, delta);
+        }
+    }
+}
+
+/// Hinge loss for classification with targets of -1 or 1, pair with `Tanh` or `Linear`.
+pub struct Hinge;
+impl Loss for Hinge {
+    fn loss_in<T: Float>(output: &[T], target: &[T]) -> T {
+        output
+            .iter()
+            .zip(target)
+            .map(|(&y, &t)| (T::one() - t * y).max(T::zero()))
+            .sum()
+    }
+    fn derivative_in<T: Float>(output: &[T], target: &[T], grad: &mut [T]) {
+        for ((g, y), t) in grad.iter_mut().zip(output).zip(target) {
+            *g = if *t * *y < T::one() { -*t } else { T::zero() };
+        }
+    }
+}
@@ -1,5 +1,9 @@
-use feoho_nn::{Arch, Result, Sigmoid};
+use std::{env, path::Path};
 
+use feoho_nn::{Arch, Result, Sigmoid, TrainConfig};
+
+/// `cargo run -- <model>` reuses the model saved at `<model>` by a previous run,
+/// or trains one and saves it there. Without an argument the model is only trained.
 fn main() -> Result<()> {
     let test_data = [
         0.0, 0.0, 0.0,
@@ -7,15 +11,33 @@ fn main() -> Result<()> {
         1.0, 0.0, 0.0,
         1.0, 1.0, 1.0
     ];
+    let model_path = env::args().nth(1);
+
+    if let Some(path) = model_path.as_deref().filter(|path| Path::new(path).exists()) {
+        let mut arch: Arch<Sigmoid> = Arch::load(path)?;
+        arch.set_data(&test_data, 4);
+        arch.print_model();
+        arch._check_model();
+        return Ok(());
+    }
+
     let arch_layers = vec![1];
     let mut arch: Arch<Sigmoid> = Arch::new(&test_data, 4, 2, 1, &arch_layers);
 
     arch.print_model();
     arch.print_given_input();
     arch.print_given_output();
-    arch.train();
+    arch.train_with(&TrainConfig {
+        rate: 1.0,
+        epochs: 10 * 1000,
+        log_interval: Some(1000),
+        ..TrainConfig::default()
+    });
     arch.print_model();
     arch._check_model();
+    if let Some(path) = &model_path {
+        arch.save(path)?;
+    }
 
     Ok(())
 }
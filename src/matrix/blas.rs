@@ -0,0 +1,98 @@
+//! `Matrix::dot` through the `cblas_dgemm` and `cblas_sgemm` of a system BLAS, enabled by the `blas` feature.
+//!
+//! The library is linked as `openblas`, it has to be installed, e.g. `libopenblas-dev`.
+
+use std::{any::Any, os::raw::c_int};
+
+use super::gemm::Operand;
+use crate::Float;
+
+const CBLAS_ROW_MAJOR: c_int = 101;
+const CBLAS_NO_TRANS: c_int = 111;
+
+type CblasGemm<T> =
+    unsafe extern "C" fn(c_int, c_int, c_int, c_int, c_int, c_int, T, *const T, c_int, *const T, c_int, T, *mut T, c_int);
+
+#[link(name = "openblas")]
+extern "C" {
+    #[allow(clippy::too_many_arguments)]
+    fn cblas_dgemm(
+        order: c_int,
+        trans_a: c_int,
+        trans_b: c_int,
+        m: c_int,
+        n: c_int,
+        k: c_int,
+        alpha: f64,
+        a: *const f64,
+        lda: c_int,
+        b: *const f64,
+        ldb: c_int,
+        beta: f64,
+        c: *mut f64,
+        ldc: c_int,
+    );
+
+    #[allow(clippy::too_many_arguments)]
+    fn cblas_sgemm(
+        order: c_int,
+        trans_a: c_int,
+        trans_b: c_int,
+        m: c_int,
+        n: c_int,
+        k: c_int,
+        alpha: f32,
+        a: *const f32,
+        lda: c_int,
+        b: *const f32,
+        ldb: c_int,
+        beta: f32,
+        c: *mut f32,
+        ldc: c_int,
+    );
+}
+
+/// The CBLAS routine for `T`, `dgemm` for `f64` and `sgemm` for `f32`.
+fn routine<T: Float>() -> Option<CblasGemm<T>> {
+    let dgemm = &(cblas_dgemm as CblasGemm<f64>) as &dyn Any;
+    let sgemm = &(cblas_sgemm as CblasGemm<f32>) as &dyn Any;
+    dgemm
+        .downcast_ref::<CblasGemm<T>>()
+        .or_else(|| sgemm.downcast_ref::<CblasGemm<T>>())
+        .copied()
+}
+
+/// Overwrites `c` with `a * b` like `gemm::gemm`, returns `false` without touching `c`
+/// if the shapes can't be passed to CBLAS, i.e. a dimension is 0 or doesn't fit a C `int`.
+pub(super) fn gemm<T: Float>(m: usize, n: usize, k: usize, a: Operand<T>, b: Operand<T>, c: &mut [T], ldc: usize) -> bool {
+    let Some(routine) = routine::<T>() else {
+        return false;
+    };
+    let dims = [m, n, k, a.stride, b.stride, ldc].map(c_int::try_from);
+    let [Ok(m), Ok(n), Ok(k), Ok(lda), Ok(ldb), Ok(ldc)] = dims else {
+        return false;
+    };
+    if m == 0 || n == 0 || k == 0 {
+        return false;
+    }
+    // SAFETY: `gemm::gemm` checked that every operand holds its rows with the given strides.
+    unsafe {
+        routine(
+            CBLAS_ROW_MAJOR,
+            CBLAS_NO_TRANS,
+            CBLAS_NO_TRANS,
+            m,
+            n,
+            k,
+            T::one(),
+            a.data.as_ptr(),
+            lda,
+            b.data.as_ptr(),
+            ldb,
+            T::zero(),
+            c.as_mut_ptr(),
+            ldc,
+        );
+    }
+    true
+}
@@ -0,0 +1,284 @@
+//! Matrix multiplication `C = A * B` on row-major buffers with a row stride.
+//!
+//! The loops are tiled so that a `KC x NC` panel of `B` and an `MC x KC` block of `A`
+//! stay in cache while they are reused, inside a tile a micro kernel keeps a `4 x 8`
+//! block of `C` in registers. On x86_64 the kernel uses AVX2 and FMA when the CPU
+//! supports them, otherwise a portable scalar kernel that the compiler can vectorize.
+//! The AVX2 kernels exist for `f32` and `f64`, they are picked by comparing the type ids.
+//! With the `parallel` feature large products split the rows of `C` into blocks of `MC`
+//! rows that are computed on different threads. With the `blas` feature the product
+//! is handed to the system CBLAS instead, see `blas`.
+
+use std::any::Any;
+
+use crate::Float;
+
+const MC: usize = 64;
+const KC: usize = 256;
+const NC: usize = 512;
+
+/// Rows and cols of `C` computed by one call of the micro kernel.
+const MR: usize = 4;
+const NR: usize = 8;
+
+/// Below this many multiply-adds the threads cost more than they save.
+#[cfg(feature = "parallel")]
+const PARALLEL_MIN_FLOPS: usize = 1 << 18;
+
+/// A row-major operand, element `(i, j)` is `data[i * stride + j]`.
+#[derive(Clone, Copy)]
+pub(super) struct Operand<'a, T> {
+    pub data: &'a [T],
+    pub stride: usize,
+}
+
+type Kernel<T> = unsafe fn(usize, *const T, usize, *const T, usize, *mut T, usize);
+
+/// Signature of `gemm` and `gemm_native`.
+pub(super) type Gemm<T> = fn(usize, usize, usize, Operand<'_, T>, Operand<'_, T>, &mut [T], usize);
+
+/// Overwrites the `m x n` matrix `c` with `a * b`, `a` is `m x k` and `b` is `k x n`.
+pub(super) fn gemm<T: Float>(m: usize, n: usize, k: usize, a: Operand<T>, b: Operand<T>, c: &mut [T], ldc: usize) {
+    check(m, n, k, a, b, c, ldc);
+
+    #[cfg(feature = "blas")]
+    if super::blas::gemm(m, n, k, a, b, c, ldc) {
+        return;
+    }
+
+    gemm_tiled(m, n, k, a, b, c, ldc);
+}
+
+/// Like `gemm`, but always with the native kernel instead of BLAS.
+#[cfg(feature = "blas")]
+pub(super) fn gemm_native<T: Float>(m: usize, n: usize, k: usize, a: Operand<T>, b: Operand<T>, c: &mut [T], ldc: usize) {
+    check(m, n, k, a, b, c, ldc);
+    gemm_tiled(m, n, k, a, b, c, ldc);
+}
+
+fn check<T>(m: usize, n: usize, k: usize, a: Operand<T>, b: Operand<T>, c: &[T], ldc: usize) {
+    assert!(fits(m, k, a.stride, a.data.len()), "ERROR: gemm operand a is too short");
+    assert!(fits(k, n, b.stride, b.data.len()), "ERROR: gemm operand b is too short");
+    assert!(fits(m, n, ldc, c.len()), "ERROR: gemm destination is too short");
+}
+
+/// The native kernel, `check` has to pass first.
+fn gemm_tiled<T: Float>(m: usize, n: usize, k: usize, a: Operand<T>, b: Operand<T>, c: &mut [T], ldc: usize) {
+    #[cfg(feature = "parallel")]
+    if m > MC && m * n * k >= PARALLEL_MIN_FLOPS {
+        use rayon::prelude::*;
+
+        c.par_chunks_mut(MC * ldc)
+            .take(m.div_ceil(MC))
+            .enumerate()
+            .for_each(|(block, c)| {
+                let ii = block * MC;
+                let a = Operand {
+                    data: &a.data[ii * a.stride..],
+                    stride: a.stride,
+                };
+                gemm_serial(MC.min(m - ii), n, k, a, b, c, ldc);
+            });
+        return;
+    }
+    gemm_serial(m, n, k, a, b, c, ldc);
+}
+
+fn gemm_serial<T: Float>(m: usize, n: usize, k: usize, a: Operand<T>, b: Operand<T>, c: &mut [T], ldc: usize) {
+    for i in 0..m {
+        c[i * ldc..i * ldc + n].fill(T::zero());
+    }
+    if k == 0 {
+        return;
+    }
+
+    let kernel = select_kernel();
+    for jj in (0..n).step_by(NC) {
+        let nb = NC.min(n - jj);
+        for pp in (0..k).step_by(KC) {
+            let kb = KC.min(k - pp);
+            for ii in (0..m).step_by(MC) {
+                let mb = MC.min(m - ii);
+                // SAFETY: the asserts in `gemm` guarantee every index of the block is in bounds.
+                unsafe { block(kernel, ii, mb, jj, nb, pp, kb, a, b, c, ldc) };
+            }
+        }
+    }
+}
+
+fn fits(rows: usize, cols: usize, stride: usize, len: usize) -> bool {
+    rows == 0 || cols == 0 || (stride >= cols && (rows - 1) * stride + cols <= len)
+}
+
+fn select_kernel<T: Float>() -> Kernel<T> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+            let simd = specialize::<T, _>(kernel_avx2_f64 as Kernel<f64>)
+                .or_else(|| specialize::<T, _>(kernel_avx2_f32 as Kernel<f32>));
+            if let Some(kernel) = simd {
+                return kernel;
+            }
+        }
+    }
+    kernel_scalar
+}
+
+/// `f` as a `Kernel<T>` if it is one, i.e. if `T` is the type `f` was written for.
+fn specialize<T: Float, F: Any + Copy>(f: F) -> Option<Kernel<T>> {
+    (&f as &dyn Any).downcast_ref::<Kernel<T>>().copied()
+}
+
+/// Accumulates `A[ii.., pp..] * B[pp.., jj..]` into `C[ii.., jj..]` for one tile.
+#[allow(clippy::too_many_arguments)]
+unsafe fn block<T: Float>(
+    kernel: Kernel<T>,
+    ii: usize,
+    mb: usize,
+    jj: usize,
+    nb: usize,
+    pp: usize,
+    kb: usize,
+    a: Operand<T>,
+    b: Operand<T>,
+    c: &mut [T],
+    ldc: usize,
+) {
+    let (lda, ldb) = (a.stride, b.stride);
+    let a_ptr = a.data.as_ptr();
+    let b_ptr = b.data.as_ptr();
+    let c_ptr = c.as_mut_ptr();
+
+    let full_rows = mb - mb % MR;
+    let full_cols = nb - nb % NR;
+    for i in (ii..ii + full_rows).step_by(MR) {
+        for j in (jj..jj + full_cols).step_by(NR) {
+            kernel(
+                kb,
+                a_ptr.add(i * lda + pp),
+                lda,
+                b_ptr.add(pp * ldb + j),
+                ldb,
+                c_ptr.add(i * ldc + j),
+                ldc,
+            );
+        }
+        // remaining cols of these rows
+        for r in i..i + MR {
+            edge(r, jj + full_cols, jj + nb, pp, kb, a_ptr, lda, b_ptr, ldb, c_ptr, ldc);
+        }
+    }
+    // remaining rows
+    for r in ii + full_rows..ii + mb {
+        edge(r, jj, jj + nb, pp, kb, a_ptr, lda, b_ptr, ldb, c_ptr, ldc);
+    }
+}
+
+/// `C[r, j0..j1] += A[r, pp..pp + kb] * B[pp..pp + kb, j0..j1]`, one row at a time.
+#[allow(clippy::too_many_arguments)]
+#[inline]
+unsafe fn edge<T: Float>(
+    r: usize,
+    j0: usize,
+    j1: usize,
+    pp: usize,
+    kb: usize,
+    a: *const T,
+    lda: usize,
+    b: *const T,
+    ldb: usize,
+    c: *mut T,
+    ldc: usize,
+) {
+    if j0 == j1 {
+        return;
+    }
+    let c_row = std::slice::from_raw_parts_mut(c.add(r * ldc + j0), j1 - j0);
+    for p in pp..pp + kb {
+        let a_rp = *a.add(r * lda + p);
+        let b_row = std::slice::from_raw_parts(b.add(p * ldb + j0), j1 - j0);
+        for (c, b) in c_row.iter_mut().zip(b_row) {
+            *c += a_rp * *b;
+        }
+    }
+}
+
+/// `C[0..4, 0..8] += A[0..4, 0..kb] * B[0..kb, 0..8]` with the block of `C` held in locals.
+unsafe fn kernel_scalar<T: Float>(kb: usize, a: *const T, lda: usize, b: *const T, ldb: usize, c: *mut T, ldc: usize) {
+    let mut acc = [[T::zero(); NR]; MR];
+    for p in 0..kb {
+        let b_row = &*(b.add(p * ldb) as *const [T; NR]);
+        for (r, acc) in acc.iter_mut().enumerate() {
+            let a_rp = *a.add(r * lda + p);
+            for (acc, b) in acc.iter_mut().zip(b_row) {
+                *acc += a_rp * *b;
+            }
+        }
+    }
+    for (r, acc) in acc.iter().enumerate() {
+        let c_row = &mut *(c.add(r * ldc) as *mut [T; NR]);
+        for (c, acc) in c_row.iter_mut().zip(acc) {
+            *c += *acc;
+        }
+    }
+}
+
+/// AVX2 + FMA version of `kernel_scalar`, 8 accumulators of 4 lanes each.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+unsafe fn kernel_avx2_f64(kb: usize, a: *const f64, lda: usize, b: *const f64, ldb: usize, c: *mut f64, ldc: usize) {
+    use std::arch::x86_64::*;
+
+    let mut c00 = _mm256_setzero_pd();
+    let mut c01 = _mm256_setzero_pd();
+    let mut c10 = _mm256_setzero_pd();
+    let mut c11 = _mm256_setzero_pd();
+    let mut c20 = _mm256_setzero_pd();
+    let mut c21 = _mm256_setzero_pd();
+    let mut c30 = _mm256_setzero_pd();
+    let mut c31 = _mm256_setzero_pd();
+    for p in 0..kb {
+        let b0 = _mm256_loadu_pd(b.add(p * ldb));
+        let b1 = _mm256_loadu_pd(b.add(p * ldb + 4));
+        let a0 = _mm256_broadcast_sd(&*a.add(p));
+        c00 = _mm256_fmadd_pd(a0, b0, c00);
+        c01 = _mm256_fmadd_pd(a0, b1, c01);
+        let a1 = _mm256_broadcast_sd(&*a.add(lda + p));
+        c10 = _mm256_fmadd_pd(a1, b0, c10);
+        c11 = _mm256_fmadd_pd(a1, b1, c11);
+        let a2 = _mm256_broadcast_sd(&*a.add(2 * lda + p));
+        c20 = _mm256_fmadd_pd(a2, b0, c20);
+        c21 = _mm256_fmadd_pd(a2, b1, c21);
+        let a3 = _mm256_broadcast_sd(&*a.add(3 * lda + p));
+        c30 = _mm256_fmadd_pd(a3, b0, c30);
+        c31 = _mm256_fmadd_pd(a3, b1, c31);
+    }
+    for (r, (lo, hi)) in [(c00, c01), (c10, c11), (c20, c21), (c30, c31)].into_iter().enumerate() {
+        let row = c.add(r * ldc);
+        _mm256_storeu_pd(row, _mm256_add_pd(_mm256_loadu_pd(row), lo));
+        _mm256_storeu_pd(row.add(4), _mm256_add_pd(_mm256_loadu_pd(row.add(4)), hi));
+    }
+}
+
+/// `f32` version of `kernel_avx2_f64`, a row of the block fits into one register of 8 lanes.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+unsafe fn kernel_avx2_f32(kb: usize, a: *const f32, lda: usize, b: *const f32, ldb: usize, c: *mut f32, ldc: usize) {
+    use std::arch::x86_64::*;
+
+    let mut c0 = _mm256_setzero_ps();
+    let mut c1 = _mm256_setzero_ps();
+    let mut c2 = _mm256_setzero_ps();
+    let mut c3 = _mm256_setzero_ps();
+    for p in 0..kb {
+        let b0 = _mm256_loadu_ps(b.add(p * ldb));
+        c0 = _mm256_fmadd_ps(_mm256_broadcast_ss(&*a.add(p)), b0, c0);
+        c1 = _mm256_fmadd_ps(_mm256_broadcast_ss(&*a.add(lda + p)), b0, c1);
+        c2 = _mm256_fmadd_ps(_mm256_broadcast_ss(&*a.add(2 * lda + p)), b0, c2);
+        c3 = _mm256_fmadd_ps(_mm256_broadcast_ss(&*a.add(3 * lda + p)), b0, c3);
+    }
+    for (r, acc) in [c0, c1, c2, c3].into_iter().enumerate() {
+        let row = c.add(r * ldc);
+        _mm256_storeu_ps(row, _mm256_add_ps(_mm256_loadu_ps(row), acc));
+    }
+}
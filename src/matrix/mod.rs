@@ -1,38 +1,92 @@
+#[cfg(feature = "blas")]
+mod blas;
+mod gemm;
+mod npy;
+mod ops;
+mod shape;
+mod view;
+
+pub use view::{MatrixView, MatrixViewMut};
+
 use std::{
     fmt::{Debug, Display},
-    ops::{AddAssign, Range},
+    ops::Range,
 };
 
 use rand::Rng;
 
-use crate::{ActivationFunction, NNET};
+use crate::{Activation, ActivationFunction, Float, NNError, Result, NNET};
 
-#[derive(Debug, Default)]
-pub struct Matrix {
+/// Below this many elements the threads cost more than they save.
+#[cfg(feature = "parallel")]
+const PARALLEL_MIN_ELEMENTS: usize = 1 << 14;
+
+/// Row-major matrix of `f64`, or of `f32` as `Matrix<f32>`.
+#[derive(Debug, Default, Clone)]
+pub struct Matrix<T = NNET> {
     rows: usize,
     cols: usize,
     stride: usize,
-    data: Vec<NNET>,
+    data: Vec<T>,
 }
 
-#[allow(unused)]
+/// Constructors of `f64` matrices, so that e.g. `Matrix::zero(2, 3)` needs no type annotation.
+/// The `_in` variants build a matrix of any element type, e.g. `Matrix::<f32>::zero_in(2, 3)`.
 impl Matrix {
     pub fn from(rows: usize, cols: usize, stride: usize, data: &[NNET]) -> Self {
-        assert!(
-            data.len() >= rows * cols,
-            "ERROR:Size of data is not equal to given rows and cols."
-        );
-        let data = data.to_vec();
-        Self {
+        Self::from_in(rows, cols, stride, data)
+    }
+
+    pub fn try_from(rows: usize, cols: usize, stride: usize, data: &[NNET]) -> Result<Self> {
+        Self::try_from_in(rows, cols, stride, data)
+    }
+
+    pub fn zero(rows: usize, cols: usize) -> Self {
+        Self::zero_in(rows, cols)
+    }
+
+    pub fn identity(rows: usize, cols: usize) -> Self {
+        Self::identity_in(rows, cols)
+    }
+}
+
+#[allow(unused)]
+impl<T: Float> Matrix<T> {
+    /// Like `from`, for any element type.
+    pub fn from_in(rows: usize, cols: usize, stride: usize, data: &[T]) -> Self {
+        Self::try_from_in(rows, cols, stride, data).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `from_in`, but returns an error if `data` is too short for
+    /// `rows` rows of `stride` elements or if `stride` is smaller than `cols`.
+    pub fn try_from_in(rows: usize, cols: usize, stride: usize, data: &[T]) -> Result<Self> {
+        // the last row does not need the padding of the stride, `None` if the size overflows
+        let needed = match rows {
+            0 => Some(0),
+            rows => (rows - 1).checked_mul(stride).and_then(|len| len.checked_add(cols)),
+        };
+        let needed = match needed {
+            Some(needed) if stride >= cols && data.len() >= needed => needed,
+            _ => {
+                return Err(NNError::ShapeMismatch {
+                    op: "from",
+                    left: (rows, stride.max(cols)),
+                    right: (1, data.len()),
+                })
+            }
+        };
+        let data = data[..needed].to_vec();
+        Ok(Self {
             rows,
             cols,
             data,
             stride,
-        }
+        })
     }
 
-    pub fn zero(rows: usize, cols: usize) -> Self {
-        let data = vec![0.0; rows * cols];
+    /// Like `zero`, for any element type.
+    pub fn zero_in(rows: usize, cols: usize) -> Self {
+        let data = vec![T::zero(); rows * cols];
         Self {
             rows,
             cols,
@@ -41,11 +95,12 @@ impl Matrix {
         }
     }
 
-    pub fn identity(rows: usize, cols: usize) -> Self {
-        let mut data = vec![0.0; rows * cols];
+    /// Like `identity`, for any element type.
+    pub fn identity_in(rows: usize, cols: usize) -> Self {
+        let mut data = vec![T::zero(); rows * cols];
         let min = usize::min(rows, cols);
         for i in 0..min {
-            data[i * cols + i] = 1.0;
+            data[i * cols + i] = T::one();
         }
         Self {
             rows,
@@ -55,20 +110,28 @@ impl Matrix {
         }
     }
 
-    pub fn random_range(&mut self, range: Range<NNET>) -> &mut Self {
-        let mut rng = rand::thread_rng();
-        for el in &mut self.data {
-            *el = rng.gen_range(range.clone());
+    pub fn random_range(&mut self, range: Range<T>) -> &mut Self {
+        self.random_range_with(range, &mut rand::thread_rng())
+    }
+
+    /// Like `random_range` but draws from `rng`, e.g. a seeded `StdRng`.
+    pub fn random_range_with<R: Rng + ?Sized>(&mut self, range: Range<T>, rng: &mut R) -> &mut Self {
+        // stays on one thread, the values have to come out of `rng` in row-major order
+        for row in 0..self.rows {
+            for el in self.get_row_ref_mut(row) {
+                *el = rng.gen_range(range.clone());
+            }
         }
         self
     }
 
     pub fn randomize(&mut self) -> &mut Self {
-        let mut rng = rand::thread_rng();
-        for el in &mut self.data {
-            *el = rng.gen_range(0.0..1.0);
-        }
-        self
+        self.randomize_with(&mut rand::thread_rng())
+    }
+
+    /// Like `randomize` but draws from `rng`, e.g. a seeded `StdRng`.
+    pub fn randomize_with<R: Rng + ?Sized>(&mut self, rng: &mut R) -> &mut Self {
+        self.random_range_with(T::zero()..T::one(), rng)
     }
 
     pub fn get_row_count(&self) -> usize {
@@ -79,7 +142,7 @@ impl Matrix {
         self.cols
     }
 
-    pub fn get_ref(&self, row: usize, col: usize) -> &NNET {
+    pub fn get_ref(&self, row: usize, col: usize) -> &T {
         assert!(
             row < self.rows,
             "ERROR: Given row {} is greater than or equal to number of rows available, i.e. {}",
@@ -96,7 +159,18 @@ impl Matrix {
         &self.data[row * self.stride + col]
     }
 
-    pub fn get_ref_mut(&mut self, row: usize, col: usize) -> &mut NNET {
+    /// Like `get_ref`, but returns an error instead of panicking.
+    pub fn try_get_ref(&self, row: usize, col: usize) -> Result<&T> {
+        if row >= self.rows || col >= self.cols {
+            return Err(NNError::IndexOutOfBounds {
+                index: (row, col),
+                shape: (self.rows, self.cols),
+            });
+        }
+        Ok(&self.data[row * self.stride + col])
+    }
+
+    pub fn get_ref_mut(&mut self, row: usize, col: usize) -> &mut T {
         assert!(
             row < self.rows,
             "ERROR: Given row {} is greater than or equal to number of rows available, i.e. {}",
@@ -112,7 +186,7 @@ impl Matrix {
         &mut self.data[row * self.stride + col]
     }
 
-    pub fn get_row_ref(&self, row: usize) -> &[NNET] {
+    pub fn get_row_ref(&self, row: usize) -> &[T] {
         assert!(
             row < self.rows,
             "ERROR: Given row {} is greater than or equal to number of rows available, i.e. {}",
@@ -123,7 +197,7 @@ impl Matrix {
         &self.data[start..(start + self.cols)]
     }
 
-    pub fn get_row_ref_mut(&mut self, row: usize) -> &mut [NNET] {
+    pub fn get_row_ref_mut(&mut self, row: usize) -> &mut [T] {
         assert!(
             row < self.rows,
             "ERROR: Given row {} is greater than or equal to number of rows available, i.e. {}",
@@ -134,83 +208,192 @@ impl Matrix {
         &mut self.data[start..(start + self.cols)]
     }
 
-    pub fn dot<'a>(&'a mut self, a: &'a Matrix, b: &'_ Matrix) {
-        assert_eq!(self.rows, a.rows);
-        assert_eq!(self.cols, b.cols);
-        assert_eq!(a.cols, b.rows);
-        for row in 0..self.rows {
-            for col in 0..self.cols {
-                *self.get_ref_mut(row, col) = 0.0;
-                for k in 0..a.cols {
-                    *self.get_ref_mut(row, col) += a.get_ref(row, k) * b.get_ref(k, col);
-                }
-            }
+    pub fn dot<'a, 'b>(&mut self, a: impl Into<MatrixView<'a, T>>, b: impl Into<MatrixView<'b, T>>) {
+        self.try_dot(a, b).unwrap_or_else(|err| panic!("ERROR: {}", err));
+    }
+
+    /// Like `dot`, but returns an error if the shapes of `a`, `b` and `self` don't fit.
+    pub fn try_dot<'a, 'b>(&mut self, a: impl Into<MatrixView<'a, T>>, b: impl Into<MatrixView<'b, T>>) -> Result<()> {
+        self.try_dot_with(a.into(), b.into(), gemm::gemm)
+    }
+
+    /// Like `dot`, but always with the native kernel instead of BLAS, e.g. to check that both agree.
+    #[cfg(feature = "blas")]
+    pub fn dot_native<'a, 'b>(&mut self, a: impl Into<MatrixView<'a, T>>, b: impl Into<MatrixView<'b, T>>) {
+        self.try_dot_with(a.into(), b.into(), gemm::gemm_native)
+            .unwrap_or_else(|err| panic!("ERROR: {}", err));
+    }
+
+    fn try_dot_with(&mut self, a: MatrixView<'_, T>, b: MatrixView<'_, T>, gemm: gemm::Gemm<T>) -> Result<()> {
+        if a.get_col_count() != b.get_row_count() {
+            return Err(NNError::ShapeMismatch {
+                op: "dot",
+                left: (a.get_row_count(), a.get_col_count()),
+                right: (b.get_row_count(), b.get_col_count()),
+            });
+        }
+        if self.rows != a.get_row_count() || self.cols != b.get_col_count() {
+            return Err(NNError::ShapeMismatch {
+                op: "dot destination",
+                left: (self.rows, self.cols),
+                right: (a.get_row_count(), b.get_col_count()),
+            });
         }
+        gemm(
+            self.rows,
+            self.cols,
+            a.cols,
+            gemm::Operand { data: a.data, stride: a.stride },
+            gemm::Operand { data: b.data, stride: b.stride },
+            &mut self.data,
+            self.stride,
+        );
+        Ok(())
     }
 
-    pub fn _dot(dest: &mut Self, a: &Matrix, b: &Matrix) {
+    /// Straightforward triple loop, kept as the reference to check and benchmark `dot` against.
+    pub fn _dot<'a, 'b>(dest: &mut Self, a: impl Into<MatrixView<'a, T>>, b: impl Into<MatrixView<'b, T>>) {
+        let (a, b) = (a.into(), b.into());
         assert_eq!(dest.rows, a.rows);
         assert_eq!(dest.cols, b.cols);
         assert_eq!(a.cols, b.rows);
         for row in 0..dest.rows {
             for col in 0..dest.cols {
-                *dest.get_ref_mut(row, col) = 0.0;
+                *dest.get_ref_mut(row, col) = T::zero();
                 for k in 0..a.cols {
-                    *dest.get_ref_mut(row, col) += a.get_ref(row, k) * b.get_ref(k, col);
+                    *dest.get_ref_mut(row, col) += *a.get_ref(row, k) * *b.get_ref(k, col);
                 }
             }
         }
     }
 
-    pub fn add<'a>(&'a mut self, src: &'_ Matrix) {
-        assert_eq!(self.rows, src.rows);
-        assert_eq!(self.cols, src.cols);
-        for (index, ele) in src.data.iter().enumerate() {
-            self.data[index] += ele;
-        }
+    pub fn add<'a>(&mut self, src: impl Into<MatrixView<'a, T>>) {
+        self.try_add(src).unwrap_or_else(|err| panic!("ERROR: {}", err));
     }
 
-    pub fn _add(dest: &mut Matrix, src: &Matrix) {
-        assert_eq!(dest.rows, src.rows);
-        assert_eq!(dest.cols, src.cols);
-        for (index, ele) in src.data.iter().enumerate() {
-            dest.data[index] += ele;
+    /// Like `add`, but returns an error if the shapes differ.
+    pub fn try_add<'a>(&mut self, src: impl Into<MatrixView<'a, T>>) -> Result<()> {
+        let src = src.into();
+        if self.rows != src.get_row_count() || self.cols != src.get_col_count() {
+            return Err(NNError::ShapeMismatch {
+                op: "add",
+                left: (self.rows, self.cols),
+                right: (src.get_row_count(), src.get_col_count()),
+            });
         }
+        self.for_each_row_mut(|row, dst| {
+            for (dst, ele) in dst.iter_mut().zip(src.get_row_ref(row)) {
+                *dst += *ele;
+            }
+        });
+        Ok(())
+    }
+
+    pub fn _add<'a>(dest: &mut Self, src: impl Into<MatrixView<'a, T>>) {
+        dest.add(src);
     }
 
-    pub fn copy_from_slice(&mut self, src: &[NNET]) {
+    /// Copies `src`, which holds `rows * cols` values in row-major order without padding.
+    pub fn copy_from_slice(&mut self, src: &[T]) {
         assert_eq!(self.rows * self.cols, src.len());
-        for (index, ele) in src.iter().enumerate() {
-            self.data[index] = *ele;
+        // a matrix without cols has nothing to copy, `chunks_exact` does not take 0
+        for (row, chunk) in src.chunks_exact(self.cols.max(1)).enumerate() {
+            self.get_row_ref_mut(row).copy_from_slice(chunk);
         }
     }
 
-    pub fn copy_from(&mut self, src: &Matrix) {
-        assert_eq!(self.rows, src.rows);
-        assert_eq!(self.cols, src.cols);
-        for (index, ele) in src.data.iter().enumerate() {
-            self.data[index] = *ele;
-        }
+    pub fn copy_from<'a>(&mut self, src: impl Into<MatrixView<'a, T>>) {
+        self.view_mut().copy_from(src);
+    }
+
+    pub fn view(&self) -> MatrixView<'_, T> {
+        MatrixView::new(&self.data, 0, self.rows, self.cols, self.stride)
+    }
+
+    pub fn view_mut(&mut self) -> MatrixViewMut<'_, T> {
+        MatrixViewMut::new(&mut self.data, 0, self.rows, self.cols, self.stride)
+    }
+
+    pub fn get_stride(&self) -> usize {
+        self.stride
+    }
+
+    /// True if the rows follow each other without padding, i.e. `stride == cols`.
+    pub fn is_contiguous(&self) -> bool {
+        self.stride == self.cols || self.rows <= 1
+    }
+
+    /// Copy of the matrix without the padding of the stride.
+    pub fn to_contiguous(&self) -> Self {
+        self.view().to_matrix()
     }
 
-    pub fn get_data_ref(&self) -> &[NNET] {
+    /// The underlying storage, for a strided matrix this includes the padding
+    /// between rows, see `is_contiguous` and `to_contiguous`.
+    pub fn get_data_ref(&self) -> &[T] {
         &self.data
     }
 
-    pub fn get_data_ref_mut(&mut self) -> &mut [NNET] {
+    /// Mutable storage, see `get_data_ref`.
+    pub fn get_data_ref_mut(&mut self) -> &mut [T] {
         &mut self.data
     }
 
-    pub fn activate<Activation: ActivationFunction>(&mut self) {
-        for x in &mut self.data {
-            *x = Activation::activate(*x);
+    pub fn activate<A: ActivationFunction>(&mut self) {
+        self.map_in_place(A::activate_in);
+    }
+
+    /// Applies `activation` to every row, including row-wise ones like `Softmax`.
+    pub fn activate_with(&mut self, activation: Activation) {
+        assert_eq!(
+            activation.pieces(),
+            1,
+            "ERROR: {:?} changes the number of cols, it can't be applied in place.",
+            activation
+        );
+        let mut z = vec![T::zero(); self.cols];
+        for row in 0..self.rows {
+            z.copy_from_slice(self.get_row_ref(row));
+            activation.activate_row_in(&z, self.get_row_ref_mut(row));
         }
     }
-    pub fn fill(&mut self, val: NNET) {
-        for x in &mut self.data {
-            *x = val;
+
+    pub fn fill(&mut self, val: T) {
+        self.map_in_place(|_| val);
+    }
+
+    /// Applies `f` to every element, skipping the padding of the stride.
+    fn map_in_place(&mut self, f: impl Fn(T) -> T + Sync) {
+        self.for_each_row_mut(|_, row| {
+            for x in row {
+                *x = f(*x);
+            }
+        });
+    }
+
+    /// Calls `f` with the index and the elements of every row. With the `parallel`
+    /// feature the rows of a large matrix are spread over threads.
+    fn for_each_row_mut(&mut self, f: impl Fn(usize, &mut [T]) + Sync) {
+        let cols = self.cols;
+        // a matrix without cols has no elements, `chunks_mut` does not take 0
+        let stride = self.stride.max(1);
+
+        #[cfg(feature = "parallel")]
+        if self.rows * cols >= PARALLEL_MIN_ELEMENTS {
+            use rayon::prelude::*;
+
+            self.data
+                .par_chunks_mut(stride)
+                .take(self.rows)
+                .enumerate()
+                .for_each(|(row, data)| f(row, &mut data[..cols]));
+            return;
+        }
+        for (row, data) in self.data.chunks_mut(stride).take(self.rows).enumerate() {
+            f(row, &mut data[..cols]);
         }
     }
+
     pub fn print(&self, name: &str, padding: usize) {
         println!("{:-padding$} {}:", " ", name, padding = padding);
         for r in 0..self.rows {
@@ -238,93 +421,8 @@ impl Matrix {
     }
 }
 
-impl AddAssign<&Matrix> for Matrix {
-    fn add_assign(&mut self, rhs: &Matrix) {
-        assert_eq!(
-            self.cols, rhs.cols,
-            "ERROR: Can't add matrix as cols size are different."
-        );
-        assert_eq!(
-            self.rows, rhs.rows,
-            "ERROR: Can't add matrix as rows size are different."
-        );
-        for (i, val) in rhs.data.iter().enumerate() {
-            self.data[i] += val;
-        }
-    }
-}
-
-// impl Add<&Matrix> for &Matrix {
-//     type Output = Matrix;
-//     fn add(self, rhs: &Matrix) -> Self::Output {
-//         assert_eq!(self.cols, rhs.cols, "ERROR: Can't add matrix as cols size are different.");
-//         assert_eq!(self.rows, rhs.rows, "ERROR: Can't add matrix as rows size are different.");
-//         let rows = self.rows;
-//         let cols = self.cols;
-//         let mut result = Matrix::zero(rows, cols);
-//         //for row in 0..rows {
-//         //    for col in 0..cols {
-//         //        *result.get_ref_mut(row, col) = self.get_ref(row, col) + rhs.get_ref(row, col);
-//         //    }
-//         //}
-//         for (i, (a, b)) in self.data.iter().zip(rhs.data.iter()).enumerate() {
-//             result.data[i] = a + b;
-//         }
-//         result
-//     }
-//
-// }
-//
-// impl Mul<&Matrix> for &Matrix {
-//     type Output = Matrix;
-//     fn mul(self, rhs: &Matrix) -> Self::Output {
-//         assert_eq!(self.cols, rhs.rows, "ERROR: Can't multiply matrix as the inner size is not equal.");
-//         let rows = self.rows;
-//         let cols = rhs.cols;
-//         let mid = self.cols;
-//         let mut result = Matrix::zero(rows, cols);
-//         for row in 0..rows {
-//             for col in 0..cols {
-//                 *result.get_ref_mut(row, col) = 0.0;
-//                 for k in 0..mid {
-//                     *result.get_ref_mut(row, col) += self.get_ref(row, k) * rhs.get_ref(k, col);
-//                 }
-//             }
-//         }
-//         result
-//     }
-// }
-
-impl Display for Matrix {
+impl<T: Float> Display for Matrix<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(
-            f,
-            "\n size: {}, row: {}, columns: {}",
-            self.rows * self.cols,
-            self.rows,
-            self.cols
-        )?;
-        for r in 0..self.rows {
-            if r == 0 {
-                write!(f, " ⌈")?;
-            } else if r == self.rows - 1 {
-                write!(f, " ⌊")?;
-            } else {
-                write!(f, " ∣")?;
-            }
-            for c in 0..self.cols {
-                // write!(f, " {:-18.16}", self.data[(r * self.cols) + c])?;
-                write!(f, " {:-8.6}", self.get_ref(r, c))?;
-            }
-            if r == 0 {
-                write!(f, " ⌉")?;
-            } else if r == self.rows - 1 {
-                write!(f, " ⌋")?;
-            } else {
-                write!(f, " ∣")?;
-            }
-            writeln!(f)?;
-        }
-        writeln!(f)
+        Display::fmt(&self.view(), f)
     }
 }
@@ -0,0 +1,324 @@
+//! NumPy `.npy` files and uncompressed `.npz` archives, see `Matrix::read_npy`.
+//!
+//! ```text
+//! magic     6 bytes   b"\x93NUMPY"
+//! version   2 bytes   major, minor
+//! len       u16 (v1) or u32 (v2), little-endian
+//! header    len bytes of a python dict, e.g. {'descr': '<f8', 'fortran_order': False, 'shape': (2, 3), }
+//! data      product of shape x descr, in C (row major) order
+//! ```
+//!
+//! Supported are the dtypes `<f8` and `<f4` with 1-D or 2-D shapes, a 1-D array
+//! is a matrix with one row. A `.npz` file is a zip of `.npy` files, one per array.
+
+use std::{fs, path::Path};
+
+use crate::{Float, Matrix, NNError, Result};
+
+const MAGIC: &[u8; 6] = b"\x93NUMPY";
+
+/// Header and data are padded to a multiple of this, like `np.save` does.
+const ALIGN: usize = 64;
+
+const ZIP_LOCAL: u32 = 0x0403_4b50;
+const ZIP_CENTRAL: u32 = 0x0201_4b50;
+const ZIP_END: u32 = 0x0605_4b50;
+const ZIP64_EXTRA: u16 = 0x0001;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dtype {
+    F8,
+    F4,
+}
+
+impl Dtype {
+    fn size(self) -> usize {
+        match self {
+            Self::F8 => 8,
+            Self::F4 => 4,
+        }
+    }
+}
+
+impl<T: Float> Matrix<T> {
+    /// Reads a `.npy` file, e.g. written by `np.save`, see the module docs for what is supported.
+    pub fn read_npy(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_npy_bytes(&fs::read(path)?)
+    }
+
+    /// Writes a `.npy` file with the dtype `<f8`, or `<f4` for `Matrix<f32>`,
+    /// and the shape `(rows, cols)`.
+    pub fn write_npy(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, self.to_npy_bytes())?;
+        Ok(())
+    }
+
+    /// Reads the array `name` of a `.npz` file written by `np.savez`, `name` is the
+    /// keyword of `np.savez` or `arr_0`, `arr_1`, ... for positional arrays.
+    /// Compressed archives (`np.savez_compressed`) are not supported.
+    pub fn read_npz(path: impl AsRef<Path>, name: &str) -> Result<Self> {
+        Self::from_npz_bytes(&fs::read(path)?, name)
+    }
+
+    /// Like `read_npy`, but takes the content of the file.
+    pub fn from_npy_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < 10 || &bytes[..6] != MAGIC {
+            return Err(npy_error("missing magic string".to_string()));
+        }
+        let (len, start): (usize, usize) = match bytes[6] {
+            1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+            2 if bytes.len() >= 12 => (u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize, 12),
+            2 => return Err(npy_error("file is too short for the header".to_string())),
+            major => return Err(npy_error(format!("version {}.{} is not supported", major, bytes[7]))),
+        };
+        let end = start
+            .checked_add(len)
+            .filter(|&end| end <= bytes.len())
+            .ok_or_else(|| npy_error("file is too short for the header".to_string()))?;
+        let header = &bytes[start..end];
+        let header = std::str::from_utf8(header).map_err(|_| npy_error("header is not valid text".to_string()))?;
+        let (dtype, rows, cols) = parse_header(header)?;
+
+        let data = &bytes[end..];
+        let expected = rows
+            .checked_mul(cols)
+            .and_then(|len| len.checked_mul(dtype.size()))
+            .ok_or_else(|| npy_error(format!("shape ({}, {}) is too large", rows, cols)))?;
+        if data.len() != expected {
+            return Err(npy_error(format!(
+                "{} bytes of data for shape ({}, {}) of `{}`",
+                data.len(),
+                rows,
+                cols,
+                descr(dtype)
+            )));
+        }
+        let values: Vec<T> = match dtype {
+            Dtype::F8 => data
+                .chunks_exact(8)
+                .map(|b| T::from_f64(f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]])))
+                .collect(),
+            Dtype::F4 => data
+                .chunks_exact(4)
+                .map(|b| T::from_f64(f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64))
+                .collect(),
+        };
+        Self::try_from_in(rows, cols, cols, &values)
+    }
+
+    /// Content of the file written by `write_npy`.
+    pub fn to_npy_bytes(&self) -> Vec<u8> {
+        let dtype = match std::mem::size_of::<T>() {
+            4 => Dtype::F4,
+            _ => Dtype::F8,
+        };
+        let mut header = format!(
+            "{{'descr': '{}', 'fortran_order': False, 'shape': ({}, {}), }}",
+            descr(dtype),
+            self.rows,
+            self.cols
+        );
+        // pad with spaces and end with a newline so that the data is aligned
+        let padding = ALIGN - (MAGIC.len() + 4 + header.len() + 1) % ALIGN;
+        header.push_str(&" ".repeat(padding % ALIGN));
+        header.push('\n');
+
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 4 + header.len() + self.rows * self.cols * dtype.size());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&[1, 0]);
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        for row in 0..self.rows {
+            for &value in self.get_row_ref(row) {
+                match dtype {
+                    Dtype::F8 => bytes.extend_from_slice(&value.as_f64().to_le_bytes()),
+                    Dtype::F4 => bytes.extend_from_slice(&(value.as_f64() as f32).to_le_bytes()),
+                }
+            }
+        }
+        bytes
+    }
+
+    /// Like `read_npz`, but takes the content of the file.
+    pub fn from_npz_bytes(bytes: &[u8], name: &str) -> Result<Self> {
+        let file = format!("{}.npy", name.strip_suffix(".npy").unwrap_or(name));
+        let entry = zip_entries(bytes)?
+            .into_iter()
+            .find(|entry| entry.name == file)
+            .ok_or_else(|| npz_error(format!("no array named `{}`", name)))?;
+        if entry.method != 0 {
+            return Err(npz_error(format!(
+                "array `{}` is compressed, only uncompressed archives (np.savez) are supported",
+                name
+            )));
+        }
+
+        // the local header repeats the name and has its own extra field
+        let local = entry.offset;
+        if read_u32(bytes, local)? != ZIP_LOCAL {
+            return Err(npz_error(format!("missing local header of `{}`", name)));
+        }
+        let start = local + 30 + read_u16(bytes, local + 26)? as usize + read_u16(bytes, local + 28)? as usize;
+        let data = start
+            .checked_add(entry.size)
+            .and_then(|end| bytes.get(start..end))
+            .ok_or_else(|| npz_error(format!("archive is too short for `{}`", name)))?;
+        Self::from_npy_bytes(data)
+    }
+}
+
+fn npy_error(msg: String) -> NNError {
+    NNError::Format(format!("npy {}", msg))
+}
+
+fn npz_error(msg: String) -> NNError {
+    NNError::Format(format!("npz {}", msg))
+}
+
+fn descr(dtype: Dtype) -> &'static str {
+    match dtype {
+        Dtype::F8 => "<f8",
+        Dtype::F4 => "<f4",
+    }
+}
+
+/// Dtype and `(rows, cols)` of a header like `{'descr': '<f8', 'fortran_order': False, 'shape': (2, 3), }`.
+fn parse_header(header: &str) -> Result<(Dtype, usize, usize)> {
+    let value = |key: &str| {
+        let start = header
+            .find(&format!("'{}':", key))
+            .ok_or_else(|| npy_error(format!("header has no `{}`", key)))?
+            + key.len()
+            + 3;
+        Ok::<&str, NNError>(header[start..].trim_start())
+    };
+
+    let descr = value("descr")?;
+    let descr = descr
+        .strip_prefix('\'')
+        .and_then(|descr| descr.split('\'').next())
+        .ok_or_else(|| npy_error("invalid `descr`".to_string()))?;
+    let dtype = match descr {
+        "<f8" => Dtype::F8,
+        "<f4" => Dtype::F4,
+        other => {
+            return Err(npy_error(format!(
+                "dtype `{}` is not supported, expected `<f8` or `<f4`",
+                other
+            )))
+        }
+    };
+
+    let fortran_order = value("fortran_order")?;
+    if fortran_order.starts_with("True") {
+        return Err(npy_error(
+            "arrays in Fortran order are not supported, save with np.ascontiguousarray".to_string(),
+        ));
+    }
+    if !fortran_order.starts_with("False") {
+        return Err(npy_error("invalid `fortran_order`".to_string()));
+    }
+
+    let shape = value("shape")?;
+    let shape = shape
+        .strip_prefix('(')
+        .and_then(|shape| shape.split(')').next())
+        .ok_or_else(|| npy_error("invalid `shape`".to_string()))?;
+    let dims = shape
+        .split(',')
+        .map(str::trim)
+        .filter(|dim| !dim.is_empty())
+        .map(|dim| dim.parse::<usize>().map_err(|_| npy_error(format!("invalid `shape` ({})", shape))))
+        .collect::<Result<Vec<usize>>>()?;
+    match dims[..] {
+        [cols] => Ok((dtype, 1, cols)),
+        [rows, cols] => Ok((dtype, rows, cols)),
+        _ => Err(npy_error(format!(
+            "{}-D arrays are not supported, only 1-D and 2-D",
+            dims.len()
+        ))),
+    }
+}
+
+/// Entry of the central directory of a zip archive.
+struct ZipEntry {
+    name: String,
+    method: u16,
+    size: usize,
+    offset: usize,
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Result<u16> {
+    match at.checked_add(2).and_then(|end| bytes.get(at..end)) {
+        Some(b) => Ok(u16::from_le_bytes([b[0], b[1]])),
+        None => Err(npz_error("archive is truncated".to_string())),
+    }
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Result<u32> {
+    match at.checked_add(4).and_then(|end| bytes.get(at..end)) {
+        Some(b) => Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+        None => Err(npz_error("archive is truncated".to_string())),
+    }
+}
+
+fn read_u64(bytes: &[u8], at: usize) -> Result<u64> {
+    Ok(read_u32(bytes, at)? as u64 | (read_u32(bytes, at.saturating_add(4))? as u64) << 32)
+}
+
+/// Every entry of the central directory, sizes and offsets of zip64 are taken
+/// from the zip64 extra field.
+fn zip_entries(bytes: &[u8]) -> Result<Vec<ZipEntry>> {
+    // the end record is the last 22 bytes, followed by a comment of at most u16::MAX bytes
+    let end = (0..=bytes.len().saturating_sub(22))
+        .rev()
+        .take(u16::MAX as usize + 1)
+        .find(|&at| read_u32(bytes, at).ok() == Some(ZIP_END))
+        .ok_or_else(|| npz_error("not a zip archive".to_string()))?;
+    let count = read_u16(bytes, end + 10)? as usize;
+    let mut at = read_u32(bytes, end + 16)? as usize;
+
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        if read_u32(bytes, at)? != ZIP_CENTRAL {
+            return Err(npz_error("invalid central directory".to_string()));
+        }
+        let method = read_u16(bytes, at + 10)?;
+        let mut size = read_u32(bytes, at + 20)? as u64;
+        let mut uncompressed = read_u32(bytes, at + 24)? as u64;
+        let name_len = read_u16(bytes, at + 28)? as usize;
+        let extra_len = read_u16(bytes, at + 30)? as usize;
+        let comment_len = read_u16(bytes, at + 32)? as usize;
+        let mut offset = read_u32(bytes, at + 42)? as u64;
+        let name = bytes
+            .get(at + 46..at + 46 + name_len)
+            .ok_or_else(|| npz_error("archive is truncated".to_string()))?;
+        let name = String::from_utf8_lossy(name).into_owned();
+
+        // zip64 extra field, holds only the values that are u32::MAX above, in this order
+        let mut extra = at + 46 + name_len;
+        let extra_end = extra + extra_len;
+        while extra + 4 <= extra_end {
+            let (id, len) = (read_u16(bytes, extra)?, read_u16(bytes, extra + 2)? as usize);
+            if id == ZIP64_EXTRA {
+                let mut field = extra + 4;
+                for value in [&mut uncompressed, &mut size, &mut offset] {
+                    if *value == u32::MAX as u64 && field + 8 <= extra + 4 + len {
+                        *value = read_u64(bytes, field)?;
+                        field += 8;
+                    }
+                }
+            }
+            extra += 4 + len;
+        }
+
+        entries.push(ZipEntry {
+            name,
+            method,
+            size: size as usize,
+            offset: offset as usize,
+        });
+        at = extra_end + comment_len;
+    }
+    Ok(entries)
+}
@@ -0,0 +1,292 @@
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use super::{Matrix, MatrixView};
+use crate::{Float, NNError, Result};
+
+impl<T: Float> Matrix<T> {
+    /// Element-wise product of `self` and `other`.
+    pub fn hadamard<'a>(&self, other: impl Into<MatrixView<'a, T>>) -> Matrix<T> {
+        self.view().hadamard(other)
+    }
+
+    /// Like `hadamard`, but returns an error if the shapes differ.
+    pub fn try_hadamard<'a>(&self, other: impl Into<MatrixView<'a, T>>) -> Result<Matrix<T>> {
+        self.view().try_hadamard(other)
+    }
+
+    fn zip_in_place(&mut self, other: MatrixView<'_, T>, op: &'static str, f: impl Fn(&mut T, T) + Sync) {
+        check_same_shape(self.view(), other, op).unwrap_or_else(|err| panic!("ERROR: {}", err));
+        self.for_each_row_mut(|row, dst| {
+            for (dst, src) in dst.iter_mut().zip(other.get_row_ref(row)) {
+                f(dst, *src);
+            }
+        });
+    }
+}
+
+impl<T: Float> MatrixView<'_, T> {
+    /// Element-wise product of `self` and `other`.
+    pub fn hadamard<'b>(&self, other: impl Into<MatrixView<'b, T>>) -> Matrix<T> {
+        self.try_hadamard(other).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `hadamard`, but returns an error if the shapes differ.
+    pub fn try_hadamard<'b>(&self, other: impl Into<MatrixView<'b, T>>) -> Result<Matrix<T>> {
+        self.zip_map(other.into(), "hadamard", |a, b| a * b)
+    }
+
+    fn map(&self, f: impl Fn(T) -> T + Sync) -> Matrix<T> {
+        let mut result = Matrix::zero_in(self.get_row_count(), self.get_col_count());
+        result.for_each_row_mut(|row, dst| {
+            for (dst, src) in dst.iter_mut().zip(self.get_row_ref(row)) {
+                *dst = f(*src);
+            }
+        });
+        result
+    }
+
+    fn zip_map(&self, other: MatrixView<'_, T>, op: &'static str, f: impl Fn(T, T) -> T + Sync) -> Result<Matrix<T>> {
+        check_same_shape(*self, other, op)?;
+        let mut result = Matrix::zero_in(self.get_row_count(), self.get_col_count());
+        result.for_each_row_mut(|row, dst| {
+            let lhs = self.get_row_ref(row);
+            let rhs = other.get_row_ref(row);
+            for (dst, (a, b)) in dst.iter_mut().zip(lhs.iter().zip(rhs)) {
+                *dst = f(*a, *b);
+            }
+        });
+        Ok(result)
+    }
+}
+
+fn check_same_shape<T: Float>(left: MatrixView<'_, T>, right: MatrixView<'_, T>, op: &'static str) -> Result<()> {
+    if left.get_row_count() != right.get_row_count() || left.get_col_count() != right.get_col_count() {
+        return Err(NNError::ShapeMismatch {
+            op,
+            left: (left.get_row_count(), left.get_col_count()),
+            right: (right.get_row_count(), right.get_col_count()),
+        });
+    }
+    Ok(())
+}
+
+impl<T: Float> Add for MatrixView<'_, T> {
+    type Output = Matrix<T>;
+    fn add(self, rhs: Self) -> Self::Output {
+        self.zip_map(rhs, "add", |a, b| a + b)
+            .unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+}
+
+impl<T: Float> Sub for MatrixView<'_, T> {
+    type Output = Matrix<T>;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.zip_map(rhs, "sub", |a, b| a - b)
+            .unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+}
+
+/// Matrix product, see `Matrix::dot`.
+impl<T: Float> Mul for MatrixView<'_, T> {
+    type Output = Matrix<T>;
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut result = Matrix::zero_in(self.get_row_count(), rhs.get_col_count());
+        result.dot(self, rhs);
+        result
+    }
+}
+
+impl<T: Float> Neg for MatrixView<'_, T> {
+    type Output = Matrix<T>;
+    fn neg(self) -> Self::Output {
+        self.map(|x| -x)
+    }
+}
+
+impl<T: Float> Mul<T> for MatrixView<'_, T> {
+    type Output = Matrix<T>;
+    fn mul(self, rhs: T) -> Self::Output {
+        self.map(|x| x * rhs)
+    }
+}
+
+impl<T: Float> Div<T> for MatrixView<'_, T> {
+    type Output = Matrix<T>;
+    fn div(self, rhs: T) -> Self::Output {
+        self.map(|x| x / rhs)
+    }
+}
+
+impl<T: Float> Add<&Matrix<T>> for &Matrix<T> {
+    type Output = Matrix<T>;
+    fn add(self, rhs: &Matrix<T>) -> Self::Output {
+        self.view() + rhs.view()
+    }
+}
+
+impl<T: Float> Sub<&Matrix<T>> for &Matrix<T> {
+    type Output = Matrix<T>;
+    fn sub(self, rhs: &Matrix<T>) -> Self::Output {
+        self.view() - rhs.view()
+    }
+}
+
+/// Matrix product, see `Matrix::dot`.
+impl<T: Float> Mul<&Matrix<T>> for &Matrix<T> {
+    type Output = Matrix<T>;
+    fn mul(self, rhs: &Matrix<T>) -> Self::Output {
+        self.view() * rhs.view()
+    }
+}
+
+impl<T: Float> AddAssign<MatrixView<'_, T>> for Matrix<T> {
+    fn add_assign(&mut self, rhs: MatrixView<'_, T>) {
+        self.zip_in_place(rhs, "add", |a, b| *a += b);
+    }
+}
+
+impl<T: Float> SubAssign<MatrixView<'_, T>> for Matrix<T> {
+    fn sub_assign(&mut self, rhs: MatrixView<'_, T>) {
+        self.zip_in_place(rhs, "sub", |a, b| *a -= b);
+    }
+}
+
+impl<T: Float> AddAssign<&Matrix<T>> for Matrix<T> {
+    fn add_assign(&mut self, rhs: &Matrix<T>) {
+        *self += rhs.view();
+    }
+}
+
+impl<T: Float> SubAssign<&Matrix<T>> for Matrix<T> {
+    fn sub_assign(&mut self, rhs: &Matrix<T>) {
+        *self -= rhs.view();
+    }
+}
+
+impl<T: Float> MulAssign<&Matrix<T>> for Matrix<T> {
+    fn mul_assign(&mut self, rhs: &Matrix<T>) {
+        *self = &*self * rhs;
+    }
+}
+
+impl<T: Float> Neg for &Matrix<T> {
+    type Output = Matrix<T>;
+    fn neg(self) -> Self::Output {
+        -self.view()
+    }
+}
+
+impl<T: Float> Neg for Matrix<T> {
+    type Output = Matrix<T>;
+    fn neg(mut self) -> Self::Output {
+        self.map_in_place(|x| -x);
+        self
+    }
+}
+
+impl<T: Float> Mul<T> for &Matrix<T> {
+    type Output = Matrix<T>;
+    fn mul(self, rhs: T) -> Self::Output {
+        self.view() * rhs
+    }
+}
+
+impl<T: Float> Mul<T> for Matrix<T> {
+    type Output = Matrix<T>;
+    fn mul(mut self, rhs: T) -> Self::Output {
+        self *= rhs;
+        self
+    }
+}
+
+// `T * Matrix<T>` can't be generic, the left operand would be an uncovered type parameter.
+macro_rules! scalar_lhs_mul {
+    ($($t:ty),*) => {$(
+        impl Mul<&Matrix<$t>> for $t {
+            type Output = Matrix<$t>;
+            fn mul(self, rhs: &Matrix<$t>) -> Self::Output {
+                rhs * self
+            }
+        }
+
+        impl Mul<Matrix<$t>> for $t {
+            type Output = Matrix<$t>;
+            fn mul(self, rhs: Matrix<$t>) -> Self::Output {
+                rhs * self
+            }
+        }
+    )*};
+}
+
+scalar_lhs_mul!(f32, f64);
+
+impl<T: Float> Div<T> for &Matrix<T> {
+    type Output = Matrix<T>;
+    fn div(self, rhs: T) -> Self::Output {
+        self.view() / rhs
+    }
+}
+
+impl<T: Float> Div<T> for Matrix<T> {
+    type Output = Matrix<T>;
+    fn div(mut self, rhs: T) -> Self::Output {
+        self /= rhs;
+        self
+    }
+}
+
+impl<T: Float> MulAssign<T> for Matrix<T> {
+    fn mul_assign(&mut self, rhs: T) {
+        self.map_in_place(|x| x * rhs);
+    }
+}
+
+impl<T: Float> DivAssign<T> for Matrix<T> {
+    fn div_assign(&mut self, rhs: T) {
+        self.map_in_place(|x| x / rhs);
+    }
+}
+
+// The owned forms forward to the borrowed ones above.
+macro_rules! forward_owned_binop {
+    ($trait:ident, $method:ident) => {
+        impl<T: Float> $trait<Matrix<T>> for Matrix<T> {
+            type Output = Matrix<T>;
+            fn $method(self, rhs: Matrix<T>) -> Self::Output {
+                (&self).$method(&rhs)
+            }
+        }
+
+        impl<T: Float> $trait<&Matrix<T>> for Matrix<T> {
+            type Output = Matrix<T>;
+            fn $method(self, rhs: &Matrix<T>) -> Self::Output {
+                (&self).$method(rhs)
+            }
+        }
+
+        impl<T: Float> $trait<Matrix<T>> for &Matrix<T> {
+            type Output = Matrix<T>;
+            fn $method(self, rhs: Matrix<T>) -> Self::Output {
+                self.$method(&rhs)
+            }
+        }
+    };
+}
+
+forward_owned_binop!(Add, add);
+forward_owned_binop!(Sub, sub);
+forward_owned_binop!(Mul, mul);
+
+macro_rules! forward_owned_assign {
+    ($trait:ident, $method:ident) => {
+        impl<T: Float> $trait<Matrix<T>> for Matrix<T> {
+            fn $method(&mut self, rhs: Matrix<T>) {
+                self.$method(&rhs);
+            }
+        }
+    };
+}
+
+forward_owned_assign!(AddAssign, add_assign);
+forward_owned_assign!(SubAssign, sub_assign);
+forward_owned_assign!(MulAssign, mul_assign);
@@ -0,0 +1,180 @@
+use std::ops::Range;
+
+use super::{Matrix, MatrixView};
+use crate::{Float, NNError, Result};
+
+/// Structural operations, each returns a new contiguous matrix.
+impl<T: Float> Matrix<T> {
+    pub fn transpose(&self) -> Matrix<T> {
+        self.view().transpose()
+    }
+
+    /// Same elements in row-major order, laid out as `rows` x `cols`.
+    pub fn reshape(&self, rows: usize, cols: usize) -> Matrix<T> {
+        self.try_reshape(rows, cols).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `reshape`, but returns an error if the element count differs.
+    pub fn try_reshape(&self, rows: usize, cols: usize) -> Result<Matrix<T>> {
+        self.view().try_reshape(rows, cols)
+    }
+
+    pub fn slice_rows(&self, range: Range<usize>) -> Matrix<T> {
+        self.try_slice_rows(range).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `slice_rows`, but returns an error if `range` is out of bounds.
+    pub fn try_slice_rows(&self, range: Range<usize>) -> Result<Matrix<T>> {
+        Ok(self.view().try_slice_rows(range)?.to_matrix())
+    }
+
+    pub fn slice_cols(&self, range: Range<usize>) -> Matrix<T> {
+        self.try_slice_cols(range).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `slice_cols`, but returns an error if `range` is out of bounds.
+    pub fn try_slice_cols(&self, range: Range<usize>) -> Result<Matrix<T>> {
+        Ok(self.view().try_slice_cols(range)?.to_matrix())
+    }
+
+    /// Row `row` as a 1 x cols matrix.
+    pub fn row(&self, row: usize) -> Matrix<T> {
+        self.view().row(row).to_matrix()
+    }
+
+    /// Column `col` as a rows x 1 matrix.
+    pub fn col(&self, col: usize) -> Matrix<T> {
+        self.view().col(col).to_matrix()
+    }
+
+    /// Splits into rows `0..mid` and `mid..rows`, e.g. for a train/test split.
+    pub fn split_at_row(&self, mid: usize) -> (Matrix<T>, Matrix<T>) {
+        let (top, bottom) = self.view().split_at_row(mid);
+        (top.to_matrix(), bottom.to_matrix())
+    }
+
+    /// Places `other` to the right of `self`.
+    pub fn hstack<'a>(&self, other: impl Into<MatrixView<'a, T>>) -> Matrix<T> {
+        self.view().hstack(other)
+    }
+
+    /// Like `hstack`, but returns an error if the row counts differ.
+    pub fn try_hstack<'a>(&self, other: impl Into<MatrixView<'a, T>>) -> Result<Matrix<T>> {
+        self.view().try_hstack(other)
+    }
+
+    /// Places `other` below `self`.
+    pub fn vstack<'a>(&self, other: impl Into<MatrixView<'a, T>>) -> Matrix<T> {
+        self.view().vstack(other)
+    }
+
+    /// Like `vstack`, but returns an error if the column counts differ.
+    pub fn try_vstack<'a>(&self, other: impl Into<MatrixView<'a, T>>) -> Result<Matrix<T>> {
+        self.view().try_vstack(other)
+    }
+}
+
+/// Views slice without copying, the other operations return a new contiguous matrix.
+impl<'a, T: Float> MatrixView<'a, T> {
+    pub fn transpose(&self) -> Matrix<T> {
+        let mut result = Matrix::zero_in(self.get_col_count(), self.get_row_count());
+        for row in 0..self.get_row_count() {
+            for (col, val) in self.get_row_ref(row).iter().enumerate() {
+                *result.get_ref_mut(col, row) = *val;
+            }
+        }
+        result
+    }
+
+    /// Same elements in row-major order, laid out as `rows` x `cols`.
+    pub fn reshape(&self, rows: usize, cols: usize) -> Matrix<T> {
+        self.try_reshape(rows, cols).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `reshape`, but returns an error if the element count differs.
+    pub fn try_reshape(&self, rows: usize, cols: usize) -> Result<Matrix<T>> {
+        // the view is backed by its elements, only the requested shape can overflow
+        if rows.checked_mul(cols) != Some(self.get_row_count() * self.get_col_count()) {
+            return Err(NNError::ShapeMismatch {
+                op: "reshape",
+                left: (self.get_row_count(), self.get_col_count()),
+                right: (rows, cols),
+            });
+        }
+        let mut result = Matrix::zero_in(rows, cols);
+        let mut dst = result.data.iter_mut();
+        for row in 0..self.get_row_count() {
+            // zip with the source first so the destination isn't advanced past the row
+            for (s, d) in self.get_row_ref(row).iter().zip(dst.by_ref()) {
+                *d = *s;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Row `row` as a 1 x cols view.
+    pub fn row(&self, row: usize) -> MatrixView<'a, T> {
+        self.slice_rows(row..row + 1)
+    }
+
+    /// Column `col` as a rows x 1 view.
+    pub fn col(&self, col: usize) -> MatrixView<'a, T> {
+        self.slice_cols(col..col + 1)
+    }
+
+    /// Splits into rows `0..mid` and `mid..rows`, e.g. for a train/test split.
+    pub fn split_at_row(&self, mid: usize) -> (MatrixView<'a, T>, MatrixView<'a, T>) {
+        (self.slice_rows(0..mid), self.slice_rows(mid..self.get_row_count()))
+    }
+
+    /// Places `other` to the right of `self`.
+    pub fn hstack<'b>(&self, other: impl Into<MatrixView<'b, T>>) -> Matrix<T> {
+        self.try_hstack(other).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `hstack`, but returns an error if the row counts differ.
+    pub fn try_hstack<'b>(&self, other: impl Into<MatrixView<'b, T>>) -> Result<Matrix<T>> {
+        let other = other.into();
+        if self.get_row_count() != other.get_row_count() {
+            return Err(NNError::ShapeMismatch {
+                op: "hstack",
+                left: (self.get_row_count(), self.get_col_count()),
+                right: (other.get_row_count(), other.get_col_count()),
+            });
+        }
+        let mut result = Matrix::zero_in(self.get_row_count(), self.get_col_count() + other.get_col_count());
+        for row in 0..self.get_row_count() {
+            let (left, right) = result.get_row_ref_mut(row).split_at_mut(self.get_col_count());
+            left.copy_from_slice(self.get_row_ref(row));
+            right.copy_from_slice(other.get_row_ref(row));
+        }
+        Ok(result)
+    }
+
+    /// Places `other` below `self`.
+    pub fn vstack<'b>(&self, other: impl Into<MatrixView<'b, T>>) -> Matrix<T> {
+        self.try_vstack(other).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `vstack`, but returns an error if the column counts differ.
+    pub fn try_vstack<'b>(&self, other: impl Into<MatrixView<'b, T>>) -> Result<Matrix<T>> {
+        let other = other.into();
+        if self.get_col_count() != other.get_col_count() {
+            return Err(NNError::ShapeMismatch {
+                op: "vstack",
+                left: (self.get_row_count(), self.get_col_count()),
+                right: (other.get_row_count(), other.get_col_count()),
+            });
+        }
+        let mut result = Matrix::zero_in(self.get_row_count() + other.get_row_count(), self.get_col_count());
+        for row in 0..self.get_row_count() {
+            result.get_row_ref_mut(row).copy_from_slice(self.get_row_ref(row));
+        }
+        for row in 0..other.get_row_count() {
+            result
+                .get_row_ref_mut(self.get_row_count() + row)
+                .copy_from_slice(other.get_row_ref(row));
+        }
+        Ok(result)
+    }
+}
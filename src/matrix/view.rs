@@ -0,0 +1,276 @@
+use std::{
+    fmt::{self, Display},
+    ops::Range,
+};
+
+use super::Matrix;
+use crate::{Float, NNError, Result, NNET};
+
+/// Borrowed, zero-copy matrix over a caller's buffer.
+///
+/// Element `(row, col)` is read from `data[offset + row * stride + col]`,
+/// so a view can select a block of columns of a wider row-major table.
+#[derive(Debug, Clone, Copy)]
+pub struct MatrixView<'a, T = NNET> {
+    pub(super) rows: usize,
+    pub(super) cols: usize,
+    pub(super) stride: usize,
+    pub(super) data: &'a [T],
+}
+
+/// Mutable counterpart of `MatrixView`.
+#[derive(Debug)]
+pub struct MatrixViewMut<'a, T = NNET> {
+    pub(super) rows: usize,
+    pub(super) cols: usize,
+    pub(super) stride: usize,
+    pub(super) data: &'a mut [T],
+}
+
+// the number of elements covered by a view, the last row does not need the padding of the stride
+fn check_layout(len: usize, offset: usize, rows: usize, cols: usize, stride: usize) -> Result<Range<usize>> {
+    let needed = match rows {
+        0 => Some(0),
+        rows => (rows - 1).checked_mul(stride).and_then(|len| len.checked_add(cols)),
+    };
+    match needed.and_then(|needed| offset.checked_add(needed)) {
+        Some(end) if stride >= cols && end <= len => Ok(offset..end),
+        _ => Err(NNError::ShapeMismatch {
+            op: "view",
+            left: (rows, stride.max(cols)),
+            right: (1, len.saturating_sub(offset)),
+        }),
+    }
+}
+
+fn row_range(rows: usize, cols: usize, stride: usize, row: usize) -> Range<usize> {
+    assert!(
+        row < rows,
+        "ERROR: Given row {} is greater than or equal to number of rows available, i.e. {}",
+        row,
+        rows
+    );
+    let start = row * stride;
+    start..start + cols
+}
+
+impl<'a, T: Float> MatrixView<'a, T> {
+    pub fn new(data: &'a [T], offset: usize, rows: usize, cols: usize, stride: usize) -> Self {
+        Self::try_new(data, offset, rows, cols, stride).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `new`, but returns an error if `data` is too short or `stride` is smaller than `cols`.
+    pub fn try_new(data: &'a [T], offset: usize, rows: usize, cols: usize, stride: usize) -> Result<Self> {
+        let range = check_layout(data.len(), offset, rows, cols, stride)?;
+        Ok(Self {
+            rows,
+            cols,
+            stride,
+            data: &data[range],
+        })
+    }
+
+    pub fn get_row_count(&self) -> usize {
+        self.rows
+    }
+
+    pub fn get_col_count(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get_ref(&self, row: usize, col: usize) -> &'a T {
+        assert!(
+            col < self.cols,
+            "ERROR: Given col {} is greater than or equal to number of cols available, i.e. {}",
+            col,
+            self.cols
+        );
+        &self.get_row_ref(row)[col]
+    }
+
+    /// Like `get_ref`, but returns an error instead of panicking.
+    pub fn try_get_ref(&self, row: usize, col: usize) -> Result<&'a T> {
+        if row >= self.rows || col >= self.cols {
+            return Err(NNError::IndexOutOfBounds {
+                index: (row, col),
+                shape: (self.rows, self.cols),
+            });
+        }
+        Ok(&self.data[row * self.stride + col])
+    }
+
+    pub fn get_row_ref(&self, row: usize) -> &'a [T] {
+        &self.data[row_range(self.rows, self.cols, self.stride, row)]
+    }
+
+    /// Rows `range` as a view into the same buffer.
+    pub fn slice_rows(&self, range: Range<usize>) -> MatrixView<'a, T> {
+        self.try_slice_rows(range).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `slice_rows`, but returns an error if `range` is out of bounds.
+    pub fn try_slice_rows(&self, range: Range<usize>) -> Result<MatrixView<'a, T>> {
+        if range.start > range.end || range.end > self.rows {
+            return Err(NNError::IndexOutOfBounds {
+                index: (range.end, 0),
+                shape: (self.rows, self.cols),
+            });
+        }
+        if range.is_empty() {
+            return Ok(MatrixView::new(&[], 0, 0, self.cols, self.stride));
+        }
+        MatrixView::try_new(self.data, range.start * self.stride, range.len(), self.cols, self.stride)
+    }
+
+    /// Cols `range` as a view into the same buffer.
+    pub fn slice_cols(&self, range: Range<usize>) -> MatrixView<'a, T> {
+        self.try_slice_cols(range).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `slice_cols`, but returns an error if `range` is out of bounds.
+    pub fn try_slice_cols(&self, range: Range<usize>) -> Result<MatrixView<'a, T>> {
+        if range.start > range.end || range.end > self.cols {
+            return Err(NNError::IndexOutOfBounds {
+                index: (0, range.end),
+                shape: (self.rows, self.cols),
+            });
+        }
+        if self.rows == 0 {
+            return Ok(MatrixView::new(&[], 0, 0, range.len(), self.stride));
+        }
+        MatrixView::try_new(self.data, range.start, self.rows, range.len(), self.stride)
+    }
+
+    /// Copies the viewed elements into a new contiguous `Matrix`.
+    pub fn to_matrix(&self) -> Matrix<T> {
+        let mut result = Matrix::zero_in(self.rows, self.cols);
+        result.copy_from(*self);
+        result
+    }
+}
+
+impl<'a, T: Float> From<&'a Matrix<T>> for MatrixView<'a, T> {
+    fn from(matrix: &'a Matrix<T>) -> Self {
+        matrix.view()
+    }
+}
+
+impl<'a, T: Float> From<&'a MatrixViewMut<'_, T>> for MatrixView<'a, T> {
+    fn from(view: &'a MatrixViewMut<'_, T>) -> Self {
+        view.as_view()
+    }
+}
+
+impl<'a, T: Float> From<&MatrixView<'a, T>> for MatrixView<'a, T> {
+    fn from(view: &MatrixView<'a, T>) -> Self {
+        *view
+    }
+}
+
+impl<'a, T: Float> MatrixViewMut<'a, T> {
+    pub fn new(data: &'a mut [T], offset: usize, rows: usize, cols: usize, stride: usize) -> Self {
+        Self::try_new(data, offset, rows, cols, stride).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `new`, but returns an error if `data` is too short or `stride` is smaller than `cols`.
+    pub fn try_new(data: &'a mut [T], offset: usize, rows: usize, cols: usize, stride: usize) -> Result<Self> {
+        let range = check_layout(data.len(), offset, rows, cols, stride)?;
+        Ok(Self {
+            rows,
+            cols,
+            stride,
+            data: &mut data[range],
+        })
+    }
+
+    pub fn as_view(&self) -> MatrixView<'_, T> {
+        MatrixView {
+            rows: self.rows,
+            cols: self.cols,
+            stride: self.stride,
+            data: self.data,
+        }
+    }
+
+    pub fn get_row_count(&self) -> usize {
+        self.rows
+    }
+
+    pub fn get_col_count(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get_ref(&self, row: usize, col: usize) -> &T {
+        self.as_view().get_ref(row, col)
+    }
+
+    pub fn get_ref_mut(&mut self, row: usize, col: usize) -> &mut T {
+        assert!(
+            col < self.cols,
+            "ERROR: Given col {} is greater than or equal to number of cols available, i.e. {}",
+            col,
+            self.cols
+        );
+        &mut self.get_row_ref_mut(row)[col]
+    }
+
+    pub fn get_row_ref(&self, row: usize) -> &[T] {
+        &self.data[row_range(self.rows, self.cols, self.stride, row)]
+    }
+
+    pub fn get_row_ref_mut(&mut self, row: usize) -> &mut [T] {
+        &mut self.data[row_range(self.rows, self.cols, self.stride, row)]
+    }
+
+    pub fn fill(&mut self, val: T) {
+        for row in 0..self.rows {
+            self.get_row_ref_mut(row).fill(val);
+        }
+    }
+
+    pub fn copy_from<'b>(&mut self, src: impl Into<MatrixView<'b, T>>) {
+        let src = src.into();
+        assert_eq!(self.rows, src.rows);
+        assert_eq!(self.cols, src.cols);
+        for row in 0..self.rows {
+            self.get_row_ref_mut(row).copy_from_slice(src.get_row_ref(row));
+        }
+    }
+
+    pub fn to_matrix(&self) -> Matrix<T> {
+        self.as_view().to_matrix()
+    }
+}
+
+impl<T: Float> Display for MatrixView<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "\n size: {}, row: {}, columns: {}",
+            self.rows * self.cols,
+            self.rows,
+            self.cols
+        )?;
+        for r in 0..self.rows {
+            if r == 0 {
+                write!(f, " ⌈")?;
+            } else if r == self.rows - 1 {
+                write!(f, " ⌊")?;
+            } else {
+                write!(f, " ∣")?;
+            }
+            for c in 0..self.cols {
+                write!(f, " {:-8.6}", self.get_ref(r, c))?;
+            }
+            if r == 0 {
+                write!(f, " ⌉")?;
+            } else if r == self.rows - 1 {
+                write!(f, " ⌋")?;
+            } else {
+                write!(f, " ∣")?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f)
+    }
+}
@@ -0,0 +1,302 @@
+//! Update rules applied to the model after every gradient computation.
+//!
+//! Every optimizer keeps its per-parameter state in `Tensor`s shaped like the model,
+//! they are created lazily on the first `step` and created again when a later `step`
+//! gets a model of a different shape. Hyperparameters are `f64` for every
+//! element type and converted on each step.
+
+use crate::{Float, Tensor, NNET};
+
+pub trait Optimizer<T: Float = NNET> {
+    /// Updates the weights and biases of `model` using `gradient`.
+    fn step(&mut self, model: &mut Tensor<T>, gradient: &Tensor<T>, rate: T);
+}
+
+/// Whether every parameter of `state` has the shape of the matching one in `model`.
+fn same_shape<T: Float>(state: &Tensor<T>, model: &Tensor<T>) -> bool {
+    state.count == model.count
+        && state.params().zip(model.params()).all(|(s, m)| {
+            s.get_row_count() == m.get_row_count() && s.get_col_count() == m.get_col_count()
+        })
+}
+
+/// The state in `slot`, zeroed if there is none yet or if it was built for a model of another shape.
+fn state_for<'a, T: Float>(slot: &'a mut Option<Tensor<T>>, model: &Tensor<T>) -> &'a mut Tensor<T> {
+    if !slot.as_ref().is_some_and(|state| same_shape(state, model)) {
+        *slot = Some(model.zero_like());
+    }
+    slot.as_mut().unwrap()
+}
+
+/// Calls `f(param, grad, state)` for every weight and bias.
+fn for_each_1<T: Float>(
+    model: &mut Tensor<T>,
+    gradient: &Tensor<T>,
+    a: &mut Tensor<T>,
+    mut f: impl FnMut(&mut T, T, &mut T),
+) {
+    for ((p, g), a) in model.params_mut().zip(gradient.params()).zip(a.params_mut()) {
+        let p = p.get_data_ref_mut().iter_mut();
+        let g = g.get_data_ref().iter();
+        let a = a.get_data_ref_mut().iter_mut();
+        for ((p, g), a) in p.zip(g).zip(a) {
+            f(p, *g, a);
+        }
+    }
+}
+
+/// Calls `f(param, grad, state_a, state_b)` for every weight and bias.
+fn for_each_2<T: Float>(
+    model: &mut Tensor<T>,
+    gradient: &Tensor<T>,
+    a: &mut Tensor<T>,
+    b: &mut Tensor<T>,
+    mut f: impl FnMut(&mut T, T, &mut T, &mut T),
+) {
+    let params = model.params_mut().zip(gradient.params());
+    for ((p, g), (a, b)) in params.zip(a.params_mut().zip(b.params_mut())) {
+        let p = p.get_data_ref_mut().iter_mut();
+        let g = g.get_data_ref().iter();
+        let a = a.get_data_ref_mut().iter_mut();
+        let b = b.get_data_ref_mut().iter_mut();
+        for ((p, g), (a, b)) in p.zip(g).zip(a.zip(b)) {
+            f(p, *g, a, b);
+        }
+    }
+}
+
+/// Plain gradient descent: `p -= rate * g`.
+#[derive(Debug, Default, Clone)]
+pub struct Sgd;
+
+impl<T: Float> Optimizer<T> for Sgd {
+    fn step(&mut self, model: &mut Tensor<T>, gradient: &Tensor<T>, rate: T) {
+        for (p, g) in model.params_mut().zip(gradient.params()) {
+            for (p, g) in p.get_data_ref_mut().iter_mut().zip(g.get_data_ref()) {
+                *p -= rate * *g;
+            }
+        }
+    }
+}
+
+/// Gradient descent with momentum: `v = momentum * v + g`, `p -= rate * v`.
+#[derive(Debug, Clone)]
+pub struct Momentum<T = NNET> {
+    pub momentum: NNET,
+    velocity: Option<Tensor<T>>,
+}
+
+impl<T: Float> Momentum<T> {
+    pub fn new(momentum: NNET) -> Self {
+        Self {
+            momentum,
+            velocity: None,
+        }
+    }
+}
+
+impl<T: Float> Default for Momentum<T> {
+    fn default() -> Self {
+        Self::new(0.9)
+    }
+}
+
+impl<T: Float> Optimizer<T> for Momentum<T> {
+    fn step(&mut self, model: &mut Tensor<T>, gradient: &Tensor<T>, rate: T) {
+        let momentum = T::from_f64(self.momentum);
+        let velocity = state_for(&mut self.velocity, model);
+        for_each_1(model, gradient, velocity, |p, g, v| {
+            *v = momentum * *v + g;
+            *p -= rate * *v;
+        });
+    }
+}
+
+/// Nesterov accelerated gradient, the step looks ahead along the updated velocity:
+/// `v = momentum * v + g`, `p -= rate * (g + momentum * v)`.
+#[derive(Debug, Clone)]
+pub struct Nesterov<T = NNET> {
+    pub momentum: NNET,
+    velocity: Option<Tensor<T>>,
+}
+
+impl<T: Float> Nesterov<T> {
+    pub fn new(momentum: NNET) -> Self {
+        Self {
+            momentum,
+            velocity: None,
+        }
+    }
+}
+
+impl<T: Float> Default for Nesterov<T> {
+    fn default() -> Self {
+        Self::new(0.9)
+    }
+}
+
+impl<T: Float> Optimizer<T> for Nesterov<T> {
+    fn step(&mut self, model: &mut Tensor<T>, gradient: &Tensor<T>, rate: T) {
+        let momentum = T::from_f64(self.momentum);
+        let velocity = state_for(&mut self.velocity, model);
+        for_each_1(model, gradient, velocity, |p, g, v| {
+            *v = momentum * *v + g;
+            *p -= rate * (g + momentum * *v);
+        });
+    }
+}
+
+/// RMSProp, divides the step by a running average of the squared gradient.
+#[derive(Debug, Clone)]
+pub struct RmsProp<T = NNET> {
+    pub decay: NNET,
+    pub eps: NNET,
+    square_avg: Option<Tensor<T>>,
+}
+
+impl<T: Float> RmsProp<T> {
+    pub fn new(decay: NNET, eps: NNET) -> Self {
+        Self {
+            decay,
+            eps,
+            square_avg: None,
+        }
+    }
+}
+
+impl<T: Float> Default for RmsProp<T> {
+    fn default() -> Self {
+        Self::new(0.99, 1e-8)
+    }
+}
+
+impl<T: Float> Optimizer<T> for RmsProp<T> {
+    fn step(&mut self, model: &mut Tensor<T>, gradient: &Tensor<T>, rate: T) {
+        let (decay, eps) = (T::from_f64(self.decay), T::from_f64(self.eps));
+        let square_avg = state_for(&mut self.square_avg, model);
+        for_each_1(model, gradient, square_avg, |p, g, s| {
+            *s = decay * *s + (T::one() - decay) * g * g;
+            *p -= rate * g / (s.sqrt() + eps);
+        });
+    }
+}
+
+/// Adagrad, divides the step by the root of the sum of all squared gradients so far.
+#[derive(Debug, Clone)]
+pub struct Adagrad<T = NNET> {
+    pub eps: NNET,
+    square_sum: Option<Tensor<T>>,
+}
+
+impl<T: Float> Adagrad<T> {
+    pub fn new(eps: NNET) -> Self {
+        Self {
+            eps,
+            square_sum: None,
+        }
+    }
+}
+
+impl<T: Float> Default for Adagrad<T> {
+    fn default() -> Self {
+        Self::new(1e-10)
+    }
+}
+
+impl<T: Float> Optimizer<T> for Adagrad<T> {
+    fn step(&mut self, model: &mut Tensor<T>, gradient: &Tensor<T>, rate: T) {
+        let eps = T::from_f64(self.eps);
+        let square_sum = state_for(&mut self.square_sum, model);
+        for_each_1(model, gradient, square_sum, |p, g, s| {
+            *s += g * g;
+            *p -= rate * g / (s.sqrt() + eps);
+        });
+    }
+}
+
+/// Adam, bias corrected running averages of the gradient and of its square.
+#[derive(Debug, Clone)]
+pub struct Adam<T = NNET> {
+    pub beta1: NNET,
+    pub beta2: NNET,
+    pub eps: NNET,
+    t: i32,
+    m: Option<Tensor<T>>,
+    v: Option<Tensor<T>>,
+}
+
+impl<T: Float> Adam<T> {
+    pub fn new(beta1: NNET, beta2: NNET, eps: NNET) -> Self {
+        Self {
+            beta1,
+            beta2,
+            eps,
+            t: 0,
+            m: None,
+            v: None,
+        }
+    }
+}
+
+impl<T: Float> Default for Adam<T> {
+    fn default() -> Self {
+        Self::new(0.9, 0.999, 1e-8)
+    }
+}
+
+impl<T: Float> Optimizer<T> for Adam<T> {
+    fn step(&mut self, model: &mut Tensor<T>, gradient: &Tensor<T>, rate: T) {
+        // the step count belongs to the state, so it restarts with it
+        if !self.m.as_ref().is_some_and(|m| same_shape(m, model)) {
+            self.t = 0;
+        }
+        self.t += 1;
+        let correction1 = T::from_f64(1.0 - self.beta1.powi(self.t));
+        let correction2 = T::from_f64(1.0 - self.beta2.powi(self.t));
+        let (beta1, beta2, eps) = (T::from_f64(self.beta1), T::from_f64(self.beta2), T::from_f64(self.eps));
+        let m = state_for(&mut self.m, model);
+        let v = state_for(&mut self.v, model);
+        for_each_2(model, gradient, m, v, |p, g, m, v| {
+            *m = beta1 * *m + (T::one() - beta1) * g;
+            *v = beta2 * *v + (T::one() - beta2) * g * g;
+            let m_hat = *m / correction1;
+            let v_hat = *v / correction2;
+            *p -= rate * m_hat / (v_hat.sqrt() + eps);
+        });
+    }
+}
+
+/// Adam with decoupled weight decay, parameters shrink by `rate * weight_decay`
+/// before the Adam step instead of adding the decay to the gradient.
+#[derive(Debug, Clone)]
+pub struct AdamW<T = NNET> {
+    pub weight_decay: NNET,
+    adam: Adam<T>,
+}
+
+impl<T: Float> AdamW<T> {
+    pub fn new(beta1: NNET, beta2: NNET, eps: NNET, weight_decay: NNET) -> Self {
+        Self {
+            weight_decay,
+            adam: Adam::new(beta1, beta2, eps),
+        }
+    }
+}
+
+impl<T: Float> Default for AdamW<T> {
+    fn default() -> Self {
+        Self::new(0.9, 0.999, 1e-8, 1e-2)
+    }
+}
+
+impl<T: Float> Optimizer<T> for AdamW<T> {
+    fn step(&mut self, model: &mut Tensor<T>, gradient: &Tensor<T>, rate: T) {
+        let decay = T::one() - rate * T::from_f64(self.weight_decay);
+        for p in model.params_mut() {
+            for p in p.get_data_ref_mut() {
+                *p *= decay;
+            }
+        }
+        self.adam.step(model, gradient, rate);
+    }
+}
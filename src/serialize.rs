@@ -0,0 +1,153 @@
+//! `serde` support, enabled with the `serde` cargo feature.
+//!
+//! - `Matrix` is stored as `{ rows, cols, stride, data }` with `data` in row major order,
+//!   a matrix is always written contiguous, i.e. `stride == cols`. A larger stride is read
+//!   with or without the padding after the last row.
+//! - `Tensor` is stored as `{ layers, wl, bl }`, the activation buffers are not saved.
+//! - `Arch` is stored as `{ activations, model }`, the dataset is not saved.
+//! - `Activation` is stored by name, e.g. `"relu"` or `"maxout(2)"`.
+
+use serde::{de::Error, ser::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Activation, Arch, Float, Loss, Matrix, Tensor, VectorActivation};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Matrix")]
+struct MatrixRepr<T> {
+    rows: usize,
+    cols: usize,
+    stride: usize,
+    data: Vec<T>,
+}
+
+impl<T: Float + Serialize> Serialize for Matrix<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut data = Vec::with_capacity(self.get_row_count() * self.get_col_count());
+        for row in 0..self.get_row_count() {
+            data.extend_from_slice(self.get_row_ref(row));
+        }
+        MatrixRepr {
+            rows: self.get_row_count(),
+            cols: self.get_col_count(),
+            stride: self.get_col_count(),
+            data,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T: Float + Deserialize<'de>> Deserialize<'de> for Matrix<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = MatrixRepr::<T>::deserialize(deserializer)?;
+        if repr.stride < repr.cols {
+            return Err(D::Error::custom(format!(
+                "stride {} is smaller than cols {}",
+                repr.stride, repr.cols
+            )));
+        }
+        // the last row may come with or without the padding of the stride
+        let padded = repr.rows.checked_mul(repr.stride);
+        let trimmed = match repr.rows {
+            0 => Some(0),
+            rows => (rows - 1).checked_mul(repr.stride).and_then(|len| len.checked_add(repr.cols)),
+        };
+        let (Some(padded), Some(trimmed)) = (padded, trimmed) else {
+            return Err(D::Error::custom(format!(
+                "{}x{} matrix with stride {} is too large",
+                repr.rows, repr.cols, repr.stride
+            )));
+        };
+        if repr.data.len() != trimmed && repr.data.len() != padded {
+            return Err(D::Error::custom(format!(
+                "{}x{} matrix with stride {} needs {} or {} elements, found {}",
+                repr.rows,
+                repr.cols,
+                repr.stride,
+                trimmed,
+                padded,
+                repr.data.len()
+            )));
+        }
+        Ok(Matrix::from_in(repr.rows, repr.cols, repr.stride, &repr.data))
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename = "Tensor")]
+struct TensorRef<'a, T: Float + Serialize> {
+    layers: Vec<usize>,
+    wl: &'a [Matrix<T>],
+    bl: &'a [Matrix<T>],
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Tensor")]
+struct TensorRepr<T: Float> {
+    layers: Vec<usize>,
+    wl: Vec<Matrix<T>>,
+    bl: Vec<Matrix<T>>,
+}
+
+impl<T: Float + Serialize> Serialize for Tensor<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        TensorRef {
+            layers: self.get_layers(),
+            wl: &self.wl,
+            bl: &self.bl,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T: Float + Deserialize<'de>> Deserialize<'de> for Tensor<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = TensorRepr::<T>::deserialize(deserializer)?;
+        Tensor::from_params(&repr.layers, &repr.wl, &repr.bl).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for Activation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Activation {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename = "Arch")]
+struct ArchRef<'a, T: Float + Serialize> {
+    activations: Vec<Activation>,
+    model: &'a Tensor<T>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Arch")]
+struct ArchRepr<T: Float> {
+    activations: Vec<Activation>,
+    model: Tensor<T>,
+}
+
+impl<A: VectorActivation, L: Loss, T: Float + Serialize> Serialize for Arch<A, L, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ArchRef {
+            activations: self.get_named_activations().map_err(S::Error::custom)?,
+            model: self.get_model(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// The output activation has to be `A`, a deserialized network has no dataset.
+impl<'de, A: VectorActivation, L: Loss, T: Float + Deserialize<'de>> Deserialize<'de> for Arch<A, L, T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = ArchRepr::<T>::deserialize(deserializer)?;
+        Arch::from_model(repr.activations, repr.model).map_err(D::Error::custom)
+    }
+}
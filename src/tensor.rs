@@ -1,52 +1,133 @@
-use std::{fmt, ops::Range};
+use std::{fmt, fs, ops::Range, path::Path};
 
-use crate::{Matrix, NNET};
+use rand::Rng;
 
-#[derive(Debug)]
-pub struct Tensor {
+use crate::{
+    format::{self, Decoder, Encoder},
+    Float, Initializer, Matrix, NNError, Result, NNET,
+};
+
+#[derive(Debug, Clone)]
+pub struct Tensor<T = NNET> {
 
     /// The number of Matrices present in each layer.
     /// Activation layer has count + 1 Matrices.
     pub(super) count: usize,
 
     /// ## Weight Layers: 
-    pub(super) wl: Vec<Matrix>,
+    pub(super) wl: Vec<Matrix<T>>,
 
     /// ## Bias layers: 
-    pub(super) bl: Vec<Matrix>,
+    pub(super) bl: Vec<Matrix<T>>,
 
     /// ## Activation layers:
     /// They are one more that the `count` variable.
     /// The first layer in al is the input
     /// Last layer is output
-    pub(super) al: Vec<Matrix>,
+    pub(super) al: Vec<Matrix<T>>,
+
+    /// ## Pre-activation layers:
+    /// Weighted sums before the activation function is applied,
+    /// i.e. `al[i + 1] = activate(zl[i])`.
+    /// Kept around so that backpropagation can evaluate the derivative.
+    pub(super) zl: Vec<Matrix<T>>,
 
 }
 
+/// Constructors of `f64` tensors, so that e.g. `Tensor::from(&[2, 1])` needs no type annotation.
+/// The `_in` variants build a tensor of any element type, e.g. `Tensor::<f32>::from_in(&[2, 1])`.
 impl Tensor {
-
     pub fn from(layers: &[usize]) -> Self {
+        Self::from_in(layers)
+    }
+
+    pub fn try_from(layers: &[usize]) -> Result<Self> {
+        Self::try_from_in(layers)
+    }
+
+    pub fn from_pieces(layers: &[usize], pieces: &[usize]) -> Self {
+        Self::from_pieces_in(layers, pieces)
+    }
+
+    pub fn try_from_pieces(layers: &[usize], pieces: &[usize]) -> Result<Self> {
+        Self::try_from_pieces_in(layers, pieces)
+    }
+
+    /// Loads a tensor written by `Tensor::save`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::load_in(path)
+    }
+}
+
+impl<T: Float> Tensor<T> {
+
+    /// Like `from`, for any element type.
+    pub fn from_in(layers: &[usize]) -> Self {
+        Self::try_from_in(layers).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    /// Like `from_in`, but returns an error for less than two layers or a layer without units.
+    pub fn try_from_in(layers: &[usize]) -> Result<Self> {
+        let pieces = vec![1; layers.len().saturating_sub(1)];
+        Self::try_from_pieces_in(layers, &pieces)
+    }
+
+    /// Like `from_in`, but layer `i + 1` computes `pieces[i]` weighted sums per unit,
+    /// as needed by `Activation::Maxout`.
+    pub fn from_pieces_in(layers: &[usize], pieces: &[usize]) -> Self {
+        Self::try_from_pieces_in(layers, pieces).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    pub fn try_from_pieces_in(layers: &[usize], pieces: &[usize]) -> Result<Self> {
+        if layers.len() < 2 {
+            return Err(NNError::InvalidLayerSpec(
+                "Layer count should not be zero!".to_string(),
+            ));
+        }
         let count = layers.len() - 1;
-        assert_ne!(count, 0, "ERROR: Layer count should not be zero!");
-        let mut wl: Vec<Matrix> = Vec::with_capacity(count);
-        let mut bl: Vec<Matrix> = Vec::with_capacity(count);
-        let mut al: Vec<Matrix> = Vec::with_capacity(count + 1);
-        al.push(Matrix::zero(1, layers[0]));
+        if pieces.len() != count {
+            return Err(NNError::InvalidLayerSpec(format!(
+                "need the pieces of {} layers, found {}",
+                count,
+                pieces.len()
+            )));
+        }
+        if let Some(i) = layers.iter().position(|size| *size == 0) {
+            return Err(NNError::InvalidLayerSpec(format!("layer {} has no units", i)));
+        }
+        if let Some(i) = pieces.iter().position(|pieces| *pieces == 0) {
+            return Err(NNError::InvalidLayerSpec(format!("layer {} has no pieces", i + 1)));
+        }
+        let mut wl: Vec<Matrix<T>> = Vec::with_capacity(count);
+        let mut bl: Vec<Matrix<T>> = Vec::with_capacity(count);
+        let mut al: Vec<Matrix<T>> = Vec::with_capacity(count + 1);
+        let mut zl: Vec<Matrix<T>> = Vec::with_capacity(count);
+        al.push(Matrix::zero_in(1, layers[0]));
         for i in 1..=count {
-            al.push(Matrix::zero(1, layers[i]));
-            bl.push(Matrix::zero(1, layers[i]));
-            wl.push(Matrix::zero(al[i - 1].get_col_count(), layers[i]));
+            let width = layers[i] * pieces[i - 1];
+            al.push(Matrix::zero_in(1, layers[i]));
+            zl.push(Matrix::zero_in(1, width));
+            bl.push(Matrix::zero_in(1, width));
+            wl.push(Matrix::zero_in(al[i - 1].get_col_count(), width));
         }
 
-        Self {
+        Ok(Self {
             count,
             wl,
             bl,
             al, // activation: std::marker::PhantomData,
-        }
+            zl,
+        })
+    }
+
+    /// A tensor with the same layer sizes as `self`, filled with zeros.
+    pub fn zero_like(&self) -> Self {
+        let mut tensor = self.clone();
+        tensor.fill(T::zero());
+        tensor
     }
 
-    pub fn fill(&mut self, val: NNET) -> &mut Self {
+    pub fn fill(&mut self, val: T) -> &mut Self {
         for w in &mut self.wl {
             w.fill(val);
         }
@@ -59,75 +140,209 @@ impl Tensor {
         self
     }
 
-    pub fn randomize_range(&mut self, range: Range<NNET>) -> &mut Self {
+    pub fn randomize_range(&mut self, range: Range<T>) -> &mut Self {
+        self.randomize_range_with(range, &mut rand::thread_rng())
+    }
+
+    /// Like `randomize_range` but draws from `rng`, e.g. a seeded `StdRng`.
+    pub fn randomize_range_with<R: Rng + ?Sized>(&mut self, range: Range<T>, rng: &mut R) -> &mut Self {
         for w in &mut self.wl {
-            w.random_range(range.clone());
+            w.random_range_with(range.clone(), rng);
         }
         for b in &mut self.bl {
-            b.random_range(range.clone());
+            b.random_range_with(range.clone(), rng);
         }
         for a in &mut self.al {
-            a.random_range(range.clone());
+            a.random_range_with(range.clone(), rng);
         }
         self
     }
 
     pub fn randomize(&mut self) -> &mut Self {
+        self.randomize_with(&mut rand::thread_rng())
+    }
+
+    /// Like `randomize` but draws from `rng`, e.g. a seeded `StdRng`.
+    pub fn randomize_with<R: Rng + ?Sized>(&mut self, rng: &mut R) -> &mut Self {
+        self.randomize_range_with(T::zero()..T::one(), rng)
+    }
+
+    /// Initializes the weights and biases of every layer with `initializer`,
+    /// see `Initializer` for the distributions.
+    pub fn initialize(&mut self, initializer: Initializer) -> &mut Self {
+        self.initialize_with(initializer, &mut rand::thread_rng())
+    }
+
+    /// Like `initialize` but draws from `rng`, e.g. a seeded `StdRng`.
+    pub fn initialize_with<R: Rng + ?Sized>(&mut self, initializer: Initializer, rng: &mut R) -> &mut Self {
         for w in &mut self.wl {
-            w.randomize();
+            initializer.init_weights(w, rng);
         }
         for b in &mut self.bl {
-            b.randomize();
-        }
-        for a in &mut self.al {
-            a.randomize();
+            initializer.init_biases(b, rng);
         }
         self
     }
 
-    // pub fn get_ref_bl_mut(&mut self, index: usize) -> &mut Matrix {
+    // pub fn get_ref_bl_mut(&mut self, index: usize) -> &mut Matrix<T> {
     //     &mut self.bl[index]
     // }
 
-    // pub fn get_ref_al_mut(&mut self, index: usize) -> &mut Matrix {
+    // pub fn get_ref_al_mut(&mut self, index: usize) -> &mut Matrix<T> {
     //     &mut self.al[index]
     // }
 
-    // pub fn get_ref_wl_mut(&mut self, index: usize) -> &mut Matrix {
+    // pub fn get_ref_wl_mut(&mut self, index: usize) -> &mut Matrix<T> {
     //     &mut self.wl[index]
     // }
 
-    // pub fn get_ref_bl(&self, index: usize) -> &Matrix {
-    //     &self.bl[index]
-    // }
+    pub fn get_ref_bl(&self, index: usize) -> &Matrix<T> {
+        &self.bl[index]
+    }
 
-    // pub fn get_ref_al(&self, index: usize) -> &Matrix {
-    //     &self.al[index]
-    // }
+    pub fn get_ref_al(&self, index: usize) -> &Matrix<T> {
+        &self.al[index]
+    }
 
-    // pub fn get_ref_wl(&self, index: usize) -> &Matrix {
-    //     &self.wl[index]
-    // }
+    pub fn get_ref_wl(&self, index: usize) -> &Matrix<T> {
+        &self.wl[index]
+    }
 
-    pub fn get_input_mut(&mut self) -> &mut Matrix {
+    pub fn get_count(&self) -> usize {
+        self.count
+    }
+
+    /// Trainable parameters, all weight layers followed by all bias layers.
+    pub(crate) fn params(&self) -> impl Iterator<Item = &Matrix<T>> {
+        self.wl.iter().chain(self.bl.iter())
+    }
+
+    pub(crate) fn params_mut(&mut self) -> impl Iterator<Item = &mut Matrix<T>> {
+        self.wl.iter_mut().chain(self.bl.iter_mut())
+    }
+
+    pub fn get_input_mut(&mut self) -> &mut Matrix<T> {
         self.al.first_mut().unwrap()
     }
 
-    pub fn get_output_mut(&mut self) -> &mut Matrix {
+    pub fn get_output_mut(&mut self) -> &mut Matrix<T> {
         self.al.last_mut().unwrap()
     }
 
-    pub fn get_input(&self) -> &Matrix {
+    pub fn get_input(&self) -> &Matrix<T> {
         self.al.first().unwrap()
     }
 
-    pub fn get_output(&self) -> &Matrix {
+    pub fn get_output(&self) -> &Matrix<T> {
         self.al.last().unwrap()
     }
 
 }
 
-impl fmt::Display for Tensor {
+impl<T: Float> Tensor<T> {
+    /// Saves the weights and biases, see `format` for the layout of the file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut encoder = Encoder::new(format::KIND_TENSOR);
+        self.encode(&mut encoder)?;
+        encoder.write(path)
+    }
+
+    /// Like `load`, for any element type, the saved `f64` values are rounded to `T`.
+    pub fn load_in(path: impl AsRef<Path>) -> Result<Self> {
+        let bytes = fs::read(path)?;
+        let mut decoder = Decoder::new(&bytes, format::KIND_TENSOR)?;
+        let tensor = Self::decode(&mut decoder)?;
+        decoder.finish()?;
+        Ok(tensor)
+    }
+
+    pub(crate) fn encode(&self, encoder: &mut Encoder) -> Result<()> {
+        encoder.put_usize(self.count)?;
+        for size in self.get_layers() {
+            encoder.put_usize(size)?;
+        }
+        for (w, b) in self.wl.iter().zip(&self.bl) {
+            encoder.put_matrix(w)?;
+            encoder.put_matrix(b)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn decode(decoder: &mut Decoder) -> Result<Self> {
+        let count = decoder.get_usize()?;
+        if count == 0 {
+            return Err(format::invalid("tensor without layers".to_string()));
+        }
+        let layers = (0..=count)
+            .map(|_| decoder.get_usize())
+            .collect::<Result<Vec<usize>>>()?;
+
+        let mut wl = Vec::with_capacity(count);
+        let mut bl = Vec::with_capacity(count);
+        for _ in 0..count {
+            wl.push(decoder.get_matrix()?);
+            bl.push(decoder.get_matrix()?);
+        }
+        Self::from_params(&layers, &wl, &bl).map_err(format::invalid)
+    }
+
+    /// Builds a tensor from stored weights and biases, checking that their shapes fit `layers`.
+    pub(crate) fn from_params(
+        layers: &[usize],
+        wl: &[Matrix<T>],
+        bl: &[Matrix<T>],
+    ) -> std::result::Result<Self, String> {
+        let count = layers.len().saturating_sub(1);
+        if count == 0 || wl.len() != count || bl.len() != count {
+            return Err(format!(
+                "{} layers need {} weight and bias layers, found {} and {}",
+                layers.len(),
+                count,
+                wl.len(),
+                bl.len()
+            ));
+        }
+
+        let mut pieces = Vec::with_capacity(count);
+        for (i, (w, b)) in wl.iter().zip(bl).enumerate() {
+            let width = w.get_col_count();
+            if layers[i + 1] == 0
+                || width % layers[i + 1] != 0
+                || w.get_row_count() != layers[i]
+                || b.get_row_count() != 1
+                || b.get_col_count() != width
+            {
+                return Err(format!(
+                    "layer {} has weights {}x{} and biases {}x{}, expected {} inputs and {} units",
+                    i,
+                    w.get_row_count(),
+                    width,
+                    b.get_row_count(),
+                    b.get_col_count(),
+                    layers[i],
+                    layers[i + 1]
+                ));
+            }
+            pieces.push(width / layers[i + 1]);
+        }
+
+        let mut tensor = Self::try_from_pieces_in(layers, &pieces).map_err(|err| err.to_string())?;
+        for (dest, src) in tensor.wl.iter_mut().zip(wl) {
+            dest.copy_from(src);
+        }
+        for (dest, src) in tensor.bl.iter_mut().zip(bl) {
+            dest.copy_from(src);
+        }
+        Ok(tensor)
+    }
+
+    /// Units of every layer, input first.
+    pub fn get_layers(&self) -> Vec<usize> {
+        self.al.iter().map(|a| a.get_col_count()).collect()
+    }
+}
+
+impl<T: Float> fmt::Display for Tensor<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "[")?;
         let padding = 2;
@@ -0,0 +1,112 @@
+use crate::{NNError, Result, NNET};
+
+/// How the gradient of the cost is computed on every training step.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GradientMethod {
+    /// Exact gradient, one forward and one backward pass per sample.
+    #[default]
+    Backprop,
+    /// Numerical estimate, one full `cost()` per weight and bias.
+    /// Slow, but useful for checking `Backprop`.
+    FiniteDiff,
+}
+
+/// Hyperparameters for `Arch::train_with`.
+///
+/// Start from `TrainConfig::default()` and override what you need:
+/// ```
+/// use feoho_nn::TrainConfig;
+/// let config = TrainConfig {
+///     rate: 1.0,
+///     epochs: 5_000,
+///     ..TrainConfig::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrainConfig {
+    /// Learning rate applied to the gradient on every step.
+    pub rate: NNET,
+
+    /// Number of passes over the whole dataset.
+    pub epochs: usize,
+
+    /// Rows per gradient step, `None` uses the whole dataset as one batch
+    /// and `Some(1)` is stochastic gradient descent. `Some(0)` is an error.
+    pub batch_size: Option<usize>,
+
+    /// Reshuffle the order of the rows before every epoch.
+    pub shuffle: bool,
+
+    /// Seed for the shuffling, `None` uses the generator of the `Arch`,
+    /// which is only reproducible if it was created with `Arch::with_seed`.
+    pub seed: Option<u64>,
+
+    pub gradient_method: GradientMethod,
+
+    /// Step used by `GradientMethod::FiniteDiff`, ignored by `Backprop`.
+    pub eps: NNET,
+
+    /// Print the initial and final cost and the cost every `n` epochs,
+    /// `None` prints nothing. `Some(0)` is an error.
+    pub log_interval: Option<usize>,
+
+    /// Stop as soon as the cost is less than or equal to this value.
+    pub target_cost: Option<NNET>,
+
+    /// Stop when an epoch improves the cost by less than this value.
+    pub min_delta: Option<NNET>,
+}
+
+impl Default for TrainConfig {
+    fn default() -> Self {
+        Self {
+            rate: 1e-2,
+            epochs: 200 * 1000,
+            batch_size: None,
+            shuffle: true,
+            seed: None,
+            gradient_method: GradientMethod::default(),
+            eps: 1e-1,
+            log_interval: None,
+            target_cost: None,
+            min_delta: None,
+        }
+    }
+}
+
+impl TrainConfig {
+    /// Errors for a value that can't be trained with.
+    pub(crate) fn validate(&self) -> Result<()> {
+        if self.batch_size == Some(0) {
+            return Err(NNError::InvalidConfig("batch_size is 0".to_string()));
+        }
+        if self.log_interval == Some(0) {
+            return Err(NNError::InvalidConfig("log_interval is 0".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Whether anything is printed during training.
+    pub(crate) fn logs(&self) -> bool {
+        self.log_interval.is_some()
+    }
+
+    /// Whether the cost has to be evaluated after `epoch`.
+    pub(crate) fn needs_cost(&self, epoch: usize) -> bool {
+        self.target_cost.is_some()
+            || self.min_delta.is_some()
+            || self.should_log(epoch)
+    }
+
+    pub(crate) fn should_log(&self, epoch: usize) -> bool {
+        self.log_interval.is_some_and(|n| n != 0 && epoch.is_multiple_of(n))
+    }
+
+    /// Whether training should stop given the cost before and after an epoch.
+    pub(crate) fn should_stop(&self, previous: NNET, current: NNET) -> bool {
+        if self.target_cost.is_some_and(|target| current <= target) {
+            return true;
+        }
+        self.min_delta.is_some_and(|delta| previous - current < delta)
+    }
+}
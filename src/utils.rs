@@ -1,7 +1,9 @@
-use std::{error, result};
+use std::result;
 
-/// Generic result type.
-pub type Result<T> = result::Result<T, Box<dyn error::Error>>;
+use crate::NNError;
+
+/// Result type of every fallible function in this crate.
+pub type Result<T> = result::Result<T, NNError>;
 
 /// Neural Network Element Type.
 pub type NNET = f64;
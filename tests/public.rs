@@ -222,6 +222,25 @@ pub mod public {
             }
         }
 
+        #[test]
+        fn randomize_with_1() {
+            use rand::{rngs::StdRng, SeedableRng};
+
+            // setup
+            let mut a = Matrix::zero(3, 4);
+            let mut b = Matrix::zero(3, 4);
+
+            // action
+            a.randomize_with(&mut StdRng::seed_from_u64(1));
+            b.randomize_with(&mut StdRng::seed_from_u64(1));
+
+            // validation
+            assert_eq!(a.get_data_ref(), b.get_data_ref());
+            for x in a.get_data_ref() {
+                assert!((0.0..1.0).contains(x));
+            }
+        }
+
         #[test]
         fn add_1(){
 
@@ -246,5 +265,2176 @@ pub mod public {
             }
         }
 
+        #[test]
+        fn operators_1() {
+
+            // setup
+            let a = Matrix::from(2, 2, 2, &[1.0, 2.0, 3.0, 4.0]);
+            let b = Matrix::from(2, 2, 2, &[4.0, 3.0, 2.0, 1.0]);
+
+            // action
+            let sum = &a + &b;
+            let diff = a.clone() - &b;
+            let product = &a * &b;
+            let neg = -&a;
+            let scaled = 2.0 * &a / 4.0;
+            let hadamard = a.hadamard(&b);
+
+            // validation
+            assert_eq!(sum.get_data_ref(), &[5.0, 5.0, 5.0, 5.0]);
+            assert_eq!(diff.get_data_ref(), &[-3.0, -1.0, 1.0, 3.0]);
+            assert_eq!(product.get_data_ref(), &[8.0, 5.0, 20.0, 13.0]);
+            assert_eq!(neg.get_data_ref(), &[-1.0, -2.0, -3.0, -4.0]);
+            assert_eq!(scaled.get_data_ref(), &[0.5, 1.0, 1.5, 2.0]);
+            assert_eq!(hadamard.get_data_ref(), &[4.0, 6.0, 6.0, 4.0]);
+        }
+
+        #[test]
+        fn operators_assign_1() {
+
+            // setup
+            let a = Matrix::from(2, 3, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+            let b = Matrix::from(3, 1, 1, &[1.0, 0.0, 1.0]);
+            let mut actual_output = Matrix::zero(2, 3);
+
+            // action
+            actual_output += &a;
+            actual_output += a.clone();
+            actual_output -= &a;
+            actual_output *= 3.0;
+            actual_output /= 2.0;
+            actual_output *= b;
+
+            // validation
+            assert_eq!(actual_output.get_row_count(), 2);
+            assert_eq!(actual_output.get_col_count(), 1);
+            assert_eq!(actual_output.get_data_ref(), &[6.0, 15.0]);
+        }
+
+        #[test]
+        fn operators_strided_1() {
+
+            // setup
+            // the padding column must not leak into the results
+            let a = Matrix::from(2, 2, 3, &[1.0, 2.0, 99.0, 3.0, 4.0]);
+            let b = Matrix::from(2, 2, 2, &[1.0, 1.0, 1.0, 1.0]);
+
+            // action
+            let sum = &a + &b;
+            let neg = -a.clone();
+
+            // validation
+            assert_eq!(sum.get_data_ref(), &[2.0, 3.0, 4.0, 5.0]);
+            assert_eq!(neg.get_row_ref(1), &[-3.0, -4.0]);
+        }
+
+        #[test]
+        #[should_panic(expected = "add")]
+        fn operators_shape_mismatch_1() {
+            let a = Matrix::zero(2, 2);
+            let b = Matrix::zero(2, 3);
+            let _ = &a + &b;
+        }
+
+        #[test]
+        fn try_hadamard_1() {
+            let a = Matrix::zero(2, 2);
+            let b = Matrix::zero(3, 2);
+            assert!(a.try_hadamard(&b).is_err());
+        }
+
+        #[test]
+        fn transpose_and_reshape_1() {
+
+            // setup
+            let a = Matrix::from(2, 3, 4, &[1.0, 2.0, 3.0, 0.0, 4.0, 5.0, 6.0]);
+
+            // action
+            let t = a.transpose();
+            let r = a.reshape(3, 2);
+
+            // validation
+            assert_eq!((t.get_row_count(), t.get_col_count()), (3, 2));
+            assert_eq!(t.get_data_ref(), &[1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+            assert_eq!((r.get_row_count(), r.get_col_count()), (3, 2));
+            assert_eq!(r.get_data_ref(), &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+            assert!(a.try_reshape(4, 2).is_err());
+            // (2^62 + 1) * 4 wraps around to the 4 elements of a 2x2 matrix
+            assert!(Matrix::zero(2, 2).try_reshape((1 << 62) + 1, 4).is_err());
+        }
+
+        #[test]
+        fn slice_and_extract_1() {
+
+            // setup
+            let a = Matrix::from(3, 3, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0]);
+
+            // action
+            let rows = a.slice_rows(1..3);
+            let cols = a.slice_cols(0..2);
+            let row = a.row(2);
+            let col = a.col(1);
+            let (train, test) = a.split_at_row(2);
+
+            // validation
+            assert_eq!(rows.get_data_ref(), &[4.0, 5.0, 6.0, 7.0, 8.0, 9.0]);
+            assert_eq!(cols.get_data_ref(), &[1.0, 2.0, 4.0, 5.0, 7.0, 8.0]);
+            assert_eq!((row.get_row_count(), row.get_col_count()), (1, 3));
+            assert_eq!(row.get_data_ref(), &[7.0, 8.0, 9.0]);
+            assert_eq!((col.get_row_count(), col.get_col_count()), (3, 1));
+            assert_eq!(col.get_data_ref(), &[2.0, 5.0, 8.0]);
+            assert_eq!(train.get_row_count(), 2);
+            assert_eq!(test.get_data_ref(), &[7.0, 8.0, 9.0]);
+            assert!(a.try_slice_rows(2..4).is_err());
+            assert!(a.try_slice_cols(0..4).is_err());
+        }
+
+        #[test]
+        fn stack_1() {
+
+            // setup
+            let a = Matrix::from(2, 1, 1, &[1.0, 2.0]);
+            let b = Matrix::from(2, 2, 2, &[3.0, 4.0, 5.0, 6.0]);
+
+            // action
+            let h = a.hstack(&b);
+            let v = b.vstack(&h.slice_cols(1..3));
+
+            // validation
+            assert_eq!((h.get_row_count(), h.get_col_count()), (2, 3));
+            assert_eq!(h.get_data_ref(), &[1.0, 3.0, 4.0, 2.0, 5.0, 6.0]);
+            assert_eq!((v.get_row_count(), v.get_col_count()), (4, 2));
+            assert_eq!(v.get_data_ref(), &[3.0, 4.0, 5.0, 6.0, 3.0, 4.0, 5.0, 6.0]);
+            assert!(a.try_vstack(&b).is_err());
+            assert!(a.try_hstack(&Matrix::zero(3, 1)).is_err());
+        }
+
+        #[test]
+        fn view_1() {
+            use feoho_nn::MatrixView;
+
+            // setup
+            // rows of 2 inputs and 1 output, as passed to `Arch::new`
+            let data = [0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 1.0, 0.0];
+            let table = MatrixView::new(&data, 0, 4, 3, 3);
+
+            // action
+            let input = table.slice_cols(0..2);
+            let output = MatrixView::new(&data, 2, 4, 1, 3);
+            let last = input.slice_rows(2..4).row(1);
+
+            // validation
+            assert_eq!((input.get_row_count(), input.get_col_count()), (4, 2));
+            assert_eq!(input.get_row_ref(1), &[0.0, 1.0]);
+            assert_eq!(output.col(0).to_matrix().get_data_ref(), &[0.0, 1.0, 1.0, 0.0]);
+            assert_eq!(last.get_row_ref(0), &[1.0, 1.0]);
+            // the views borrow `data` instead of copying it
+            assert!(std::ptr::eq(input.get_ref(0, 0), &data[0]));
+            assert!(std::ptr::eq(output.get_ref(3, 0), &data[11]));
+            assert!(MatrixView::try_new(&data, 2, 4, 2, 3).is_err());
+            assert!(MatrixView::try_new(&data, 0, 2, 4, 3).is_err());
+            assert!(MatrixView::try_new(&data, 0, usize::MAX, 2, 2).is_err());
+            assert!(MatrixView::try_new(&data, usize::MAX, 1, 2, 2).is_err());
+        }
+
+        #[test]
+        fn view_matrix_ops_1() {
+            use feoho_nn::MatrixView;
+
+            // setup
+            let data = [1.0, 2.0, 9.0, 3.0, 4.0, 9.0];
+            let a = MatrixView::new(&data, 0, 2, 2, 3);
+            let b = Matrix::from(2, 2, 2, &[1.0, 0.0, 0.0, 1.0]);
+            let mut actual_output = Matrix::zero(2, 2);
+
+            // action
+            actual_output.dot(a, &b);
+            actual_output.add(a);
+            let transposed = a.transpose();
+            let stacked = b.hstack(a);
+            let sum = a + b.view();
+            let product = b.hadamard(a);
+
+            // validation
+            assert_eq!(actual_output.get_data_ref(), &[2.0, 4.0, 6.0, 8.0]);
+            assert_eq!(transposed.get_data_ref(), &[1.0, 3.0, 2.0, 4.0]);
+            assert_eq!(stacked.get_data_ref(), &[1.0, 0.0, 1.0, 2.0, 0.0, 1.0, 3.0, 4.0]);
+            assert_eq!(sum.get_data_ref(), &[2.0, 2.0, 3.0, 5.0]);
+            assert_eq!(product.get_data_ref(), &[1.0, 0.0, 0.0, 4.0]);
+            assert_eq!(a.to_matrix().get_data_ref(), &[1.0, 2.0, 3.0, 4.0]);
+        }
+
+        #[test]
+        fn view_mut_1() {
+            use feoho_nn::MatrixViewMut;
+
+            // setup
+            let mut data = [0.0; 6];
+            let src = Matrix::from(2, 2, 2, &[1.0, 2.0, 3.0, 4.0]);
+
+            // action
+            {
+                let mut view = MatrixViewMut::new(&mut data, 1, 2, 2, 3);
+                view.copy_from(&src);
+                *view.get_ref_mut(1, 1) = 5.0;
+            }
+
+            // validation
+            assert_eq!(data, [0.0, 1.0, 2.0, 0.0, 3.0, 5.0]);
+        }
+
+        #[test]
+        fn dot_matches_reference_1() {
+            use rand::{rngs::StdRng, SeedableRng};
+
+            let mut rng = StdRng::seed_from_u64(7);
+            // sizes around the tile and micro kernel edges, the last one crosses a k block
+            for &(m, k, n) in &[(1, 1, 1), (1, 3, 2), (4, 8, 8), (5, 9, 13), (67, 300, 131), (130, 70, 520)] {
+
+                // setup
+                let mut a = Matrix::zero(m, k);
+                let mut b = Matrix::zero(k, n);
+                a.random_range_with(-1.0..1.0, &mut rng);
+                b.random_range_with(-1.0..1.0, &mut rng);
+                let mut expected_output = Matrix::zero(m, n);
+                let mut actual_output = Matrix::zero(m, n);
+
+                // action
+                Matrix::_dot(&mut expected_output, &a, &b);
+                actual_output.dot(&a, &b);
+
+                // validation
+                for (ac, ex) in actual_output.get_data_ref().iter().zip(expected_output.get_data_ref()) {
+                    assert!((ac - ex).abs() < 1e-9, "{}x{}x{}: {} != {}", m, k, n, ac, ex);
+                }
+            }
+        }
+
+        #[test]
+        fn dot_matches_reference_f32_1() {
+            use rand::{rngs::StdRng, SeedableRng};
+
+            let mut rng = StdRng::seed_from_u64(7);
+            for &(m, k, n) in &[(1, 1, 1), (4, 8, 8), (5, 9, 13), (67, 300, 131)] {
+
+                // setup
+                let mut a: Matrix<f32> = Matrix::zero_in(m, k);
+                let mut b = Matrix::zero_in(k, n);
+                a.random_range_with(-1.0..1.0, &mut rng);
+                b.random_range_with(-1.0..1.0, &mut rng);
+                let mut expected_output = Matrix::zero_in(m, n);
+                let mut actual_output = Matrix::zero_in(m, n);
+
+                // action
+                Matrix::_dot(&mut expected_output, &a, &b);
+                actual_output.dot(&a, &b);
+
+                // validation
+                for (ac, ex) in actual_output.get_data_ref().iter().zip(expected_output.get_data_ref()) {
+                    assert!((ac - ex).abs() < 1e-4, "{}x{}x{}: {} != {}", m, k, n, ac, ex);
+                }
+            }
+        }
+
+        #[test]
+        fn dot_matches_reference_strided_1() {
+            use feoho_nn::MatrixView;
+            use rand::{rngs::StdRng, SeedableRng};
+
+            // setup
+            let mut table = Matrix::zero(37, 29);
+            table.random_range_with(-1.0..1.0, &mut StdRng::seed_from_u64(3));
+            let a = table.view().slice_cols(3..20);
+            let b: MatrixView = table.view().slice_rows(5..22).slice_cols(1..26);
+            let mut expected_output = Matrix::zero(37, 25);
+            let mut actual_output = Matrix::from(37, 25, 30, &vec![99.0; 36 * 30 + 25]);
+
+            // action
+            Matrix::_dot(&mut expected_output, a, b);
+            actual_output.dot(a, b);
+
+            // validation
+            for row in 0..37 {
+                for (ac, ex) in actual_output.get_row_ref(row).iter().zip(expected_output.get_row_ref(row)) {
+                    assert!((ac - ex).abs() < 1e-9, "{} != {}", ac, ex);
+                }
+            }
+            assert_eq!(actual_output.get_data_ref()[25], 99.0);
+        }
+
+        #[test]
+        fn elementwise_large_1() {
+
+            // setup
+            // large enough for the `parallel` feature to spread the rows over threads
+            let (rows, cols) = (300, 70);
+            let values: Vec<f64> = (0..rows * (cols + 1)).map(|i| (i % 97) as f64).collect();
+            let a = Matrix::from(rows, cols, cols + 1, &values);
+            let b = a.to_contiguous();
+
+            // action
+            let sum = &a + &b;
+            let mut scaled = a.clone();
+            scaled *= 0.5;
+            let mut filled = a.clone();
+            filled.fill(1.0);
+
+            // validation
+            for row in 0..rows {
+                for col in 0..cols {
+                    let x = values[row * (cols + 1) + col];
+                    assert_eq!(*sum.get_ref(row, col), 2.0 * x);
+                    assert_eq!(*scaled.get_ref(row, col), 0.5 * x);
+                    assert_eq!(*filled.get_ref(row, col), 1.0);
+                }
+            }
+            // the padding stays untouched
+            assert_eq!(scaled.get_data_ref()[cols], values[cols]);
+            assert_eq!(filled.get_data_ref()[cols], values[cols]);
+        }
+
+        #[cfg(feature = "blas")]
+        #[test]
+        fn blas_matches_native_1() {
+            use rand::{rngs::StdRng, SeedableRng};
+
+            let mut rng = StdRng::seed_from_u64(11);
+            for &(m, k, n) in &[(1, 5, 3), (64, 64, 64), (100, 257, 33)] {
+
+                // setup
+                let mut a = Matrix::zero(m, k + 2);
+                let mut b = Matrix::zero(k, n);
+                a.random_range_with(-1.0..1.0, &mut rng);
+                b.random_range_with(-1.0..1.0, &mut rng);
+                // strided operand, CBLAS gets the stride as leading dimension
+                let a = a.view().slice_cols(1..k + 1);
+                let mut native = Matrix::zero(m, n);
+                let mut blas = Matrix::zero(m, n);
+
+                // action
+                native.dot_native(a, &b);
+                blas.dot(a, &b);
+
+                // validation
+                for (ac, ex) in blas.get_data_ref().iter().zip(native.get_data_ref()) {
+                    assert!((ac - ex).abs() < 1e-9, "{}x{}x{}: {} != {}", m, k, n, ac, ex);
+                }
+            }
+        }
+
+        #[cfg(feature = "blas")]
+        #[test]
+        fn blas_matches_native_f32_1() {
+            use rand::{rngs::StdRng, SeedableRng};
+
+            // setup
+            let mut rng = StdRng::seed_from_u64(11);
+            let mut a: Matrix<f32> = Matrix::zero_in(33, 70);
+            let mut b = Matrix::zero_in(70, 17);
+            a.random_range_with(-1.0..1.0, &mut rng);
+            b.random_range_with(-1.0..1.0, &mut rng);
+            let mut native = Matrix::zero_in(33, 17);
+            let mut blas = Matrix::zero_in(33, 17);
+
+            // action
+            native.dot_native(&a, &b);
+            blas.dot(&a, &b);
+
+            // validation
+            for (ac, ex) in blas.get_data_ref().iter().zip(native.get_data_ref()) {
+                assert!((ac - ex).abs() < 1e-4, "{} != {}", ac, ex);
+            }
+        }
+
+        // a 2x2 matrix with stride 3, the padding holds 99.0 and must never be read or written
+        fn strided() -> Matrix {
+            Matrix::from(2, 2, 3, &[1.0, 2.0, 99.0, 3.0, 4.0])
+        }
+
+        fn assert_rows(matrix: &Matrix, expected: &[[f64; 2]; 2]) {
+            for (row, expected) in expected.iter().enumerate() {
+                assert_eq!(matrix.get_row_ref(row), expected);
+            }
+        }
+
+        fn assert_padding(matrix: &Matrix) {
+            assert_eq!(matrix.get_stride(), 3);
+            assert_eq!(matrix.get_data_ref()[2], 99.0);
+        }
+
+        #[test]
+        fn strided_to_contiguous_1() {
+            let a = strided();
+            assert!(!a.is_contiguous());
+            let c = a.to_contiguous();
+            assert!(c.is_contiguous());
+            assert_eq!(c.get_data_ref(), &[1.0, 2.0, 3.0, 4.0]);
+        }
+
+        #[test]
+        fn strided_add_1() {
+
+            // setup
+            let mut a = strided();
+            let b = strided();
+
+            // action
+            a.add(&b);
+
+            // validation
+            assert_rows(&a, &[[2.0, 4.0], [6.0, 8.0]]);
+            assert_padding(&a);
+        }
+
+        #[test]
+        fn strided_add_assign_1() {
+
+            // setup
+            let mut a = strided();
+            let mut b = Matrix::zero(2, 2);
+
+            // action
+            a += &Matrix::from(2, 2, 2, &[1.0, 1.0, 1.0, 1.0]);
+            b += &strided();
+
+            // validation
+            assert_rows(&a, &[[2.0, 3.0], [4.0, 5.0]]);
+            assert_padding(&a);
+            assert_eq!(b.get_data_ref(), &[1.0, 2.0, 3.0, 4.0]);
+        }
+
+        #[test]
+        fn strided_copy_from_1() {
+
+            // setup
+            let mut a = strided();
+            let mut b = Matrix::zero(2, 2);
+
+            // action
+            a.copy_from(&Matrix::from(2, 2, 2, &[5.0, 6.0, 7.0, 8.0]));
+            b.copy_from(&strided());
+
+            // validation
+            assert_rows(&a, &[[5.0, 6.0], [7.0, 8.0]]);
+            assert_padding(&a);
+            assert_eq!(b.get_data_ref(), &[1.0, 2.0, 3.0, 4.0]);
+        }
+
+        #[test]
+        fn strided_copy_from_slice_1() {
+
+            // setup
+            let mut a = strided();
+
+            // action
+            a.copy_from_slice(&[5.0, 6.0, 7.0, 8.0]);
+
+            // validation
+            assert_rows(&a, &[[5.0, 6.0], [7.0, 8.0]]);
+            assert_padding(&a);
+        }
+
+        #[test]
+        fn strided_fill_1() {
+
+            // setup
+            let mut a = strided();
+
+            // action
+            a.fill(0.5);
+
+            // validation
+            assert_rows(&a, &[[0.5, 0.5], [0.5, 0.5]]);
+            assert_padding(&a);
+        }
+
+        #[test]
+        fn strided_activate_1() {
+            use feoho_nn::{Activation, ReLU};
+
+            // setup
+            let mut a = Matrix::from(2, 2, 3, &[-1.0, 2.0, 99.0, 3.0, -4.0]);
+            let mut b = Matrix::from(2, 2, 3, &[-1.0, 2.0, 99.0, 3.0, -4.0]);
+
+            // action
+            a.activate::<ReLU>();
+            b.activate_with(Activation::ReLU);
+
+            // validation
+            assert_rows(&a, &[[0.0, 2.0], [3.0, 0.0]]);
+            assert_rows(&b, &[[0.0, 2.0], [3.0, 0.0]]);
+            assert_padding(&a);
+            assert_padding(&b);
+        }
+
+        #[test]
+        fn strided_randomize_1() {
+            use rand::{rngs::StdRng, SeedableRng};
+
+            // setup
+            let mut a = strided();
+
+            // action
+            a.random_range_with(-1.0..1.0, &mut StdRng::seed_from_u64(1));
+
+            // validation
+            for row in 0..2 {
+                for x in a.get_row_ref(row) {
+                    assert!((-1.0..1.0).contains(x));
+                }
+            }
+            assert_padding(&a);
+        }
+
+        #[test]
+        fn strided_dot_1() {
+
+            // setup
+            let a = strided();
+            let mut actual_output = Matrix::from(2, 2, 3, &[0.0, 0.0, 99.0, 0.0, 0.0]);
+
+            // action
+            actual_output.dot(&a, &a);
+
+            // validation
+            assert_rows(&actual_output, &[[7.0, 10.0], [15.0, 22.0]]);
+            assert_padding(&actual_output);
+        }
+
+        #[test]
+        fn strided_operators_1() {
+
+            // setup
+            let a = strided();
+            let mut b = strided();
+
+            // action
+            let sum = &a + &a;
+            let scaled = &a * 2.0;
+            let transposed = a.transpose();
+            b *= 2.0;
+            b -= &a;
+
+            // validation
+            assert_eq!(sum.get_data_ref(), &[2.0, 4.0, 6.0, 8.0]);
+            assert_eq!(scaled.get_data_ref(), &[2.0, 4.0, 6.0, 8.0]);
+            assert_eq!(transposed.get_data_ref(), &[1.0, 3.0, 2.0, 4.0]);
+            assert_rows(&b, &[[1.0, 2.0], [3.0, 4.0]]);
+            assert_padding(&b);
+        }
+
+    }
+
+    pub mod arch {
+        use feoho_nn::{
+            Activation, ActivationFunction, Adam, Arch, BinaryCrossEntropy, CategoricalCrossEntropy, Dataset,
+            Float, Initializer, Layer, Linear, Loss, LogSoftmax, Matrix, Mse, NNError, NegativeLogLikelihood, ReLU,
+            Sigmoid, Softmax, TrainConfig, VectorActivation,
+        };
+
+        fn assert_backprop_matches_finite_diff<A: VectorActivation, L: Loss>(arch: &mut Arch<A, L>) {
+            arch.backprop();
+            let gradient = arch.get_gradient();
+            let expected_wl: Vec<Vec<f64>> = (0..gradient.get_count()).map(|i| gradient.get_ref_wl(i).get_data_ref().to_vec()).collect();
+            let expected_bl: Vec<Vec<f64>> = (0..gradient.get_count()).map(|i| gradient.get_ref_bl(i).get_data_ref().to_vec()).collect();
+            arch.finite_diff(1e-6);
+            let gradient = arch.get_gradient();
+            for (i, (expected_wl, expected_bl)) in expected_wl.iter().zip(&expected_bl).enumerate() {
+                for (ac, ex) in gradient.get_ref_wl(i).get_data_ref().iter().zip(expected_wl) {
+                    assert!((ac - ex).abs() < 1e-4, "wl{}: {} != {}", i, ac, ex);
+                }
+                for (ac, ex) in gradient.get_ref_bl(i).get_data_ref().iter().zip(expected_bl) {
+                    assert!((ac - ex).abs() < 1e-4, "bl{}: {} != {}", i, ac, ex);
+                }
+            }
+        }
+
+        #[test]
+        fn backprop_sigmoid_1() {
+
+            // setup
+            let data = [0.3, 0.7, 1.0];
+            let mut arch: Arch<Sigmoid> = Arch::new(&data, 1, 2, 1, &[3, 2]);
+
+            // action and validation
+            assert_backprop_matches_finite_diff(&mut arch);
+        }
+
+        #[test]
+        fn backprop_tanh_linear_1() {
+
+            // setup
+            // tanh hidden layer with a linear output
+            let data = [0.3, 0.7, 2.5];
+            let mut arch: Arch<Linear> = Arch::new(&data, 1, 2, 1, &[Layer::with(3, Activation::Tanh)]);
+
+            // action and validation
+            assert_backprop_matches_finite_diff(&mut arch);
+        }
+
+        #[test]
+        fn backprop_several_rows_1() {
+
+            // setup
+            // several rows so that the cost has to accumulate over rows and columns
+            let data = [
+                0.0, 0.0, 0.0, 1.0,
+                0.0, 1.0, 0.0, 0.0,
+                1.0, 0.0, 0.0, 0.0,
+                1.0, 1.0, 1.0, 1.0,
+            ];
+            let mut arch: Arch<Sigmoid, BinaryCrossEntropy> = Arch::new(&data, 4, 2, 2, &[3]);
+
+            // action and validation
+            assert_backprop_matches_finite_diff(&mut arch);
+        }
+
+        #[test]
+        fn softmax_output_1() {
+
+            // setup
+            let data = [
+                0.1, 0.9, 1.0, 0.0, 0.0,
+                0.8, 0.2, 0.0, 1.0, 0.0,
+                0.5, 0.5, 0.0, 0.0, 1.0,
+            ];
+            let mut softmax: Arch<Softmax, CategoricalCrossEntropy> = Arch::new(&data, 3, 2, 3, &[Layer::with(4, Activation::Tanh)]);
+            let mut log_softmax: Arch<LogSoftmax, NegativeLogLikelihood> = Arch::new(&data, 3, 2, 3, &[Layer::with(4, Activation::Tanh)]);
+
+            // action and validation
+            assert_backprop_matches_finite_diff(&mut softmax);
+            assert_backprop_matches_finite_diff(&mut log_softmax);
+        }
+
+        #[test]
+        fn softmax_confidently_wrong_1() {
+
+            // setup
+            // one input and no hidden layer, the input is chosen so that class 0 wins by `gap`
+            let data: [f32; 3] = [1.0, 0.0, 1.0];
+            let mut arch: Arch<Softmax, CategoricalCrossEntropy, f32> =
+                Arch::with_seed(&data, 1, 1, 2, &[0usize; 0], Initializer::default(), 5);
+            let (w, b) = (arch.get_model().get_ref_wl(0), arch.get_model().get_ref_bl(0));
+            let dw = *w.get_ref(0, 0) - *w.get_ref(0, 1);
+            let db = *b.get_ref(0, 0) - *b.get_ref(0, 1);
+
+            for gap in [18.0, 30.0, 100.0] {
+                // action
+                arch.set_data(&[(gap - db) / dw, 0.0, 1.0], 1);
+                arch.backprop();
+
+                // validation
+                // dL/dz is y - t, about -1 for the target class however small its probability
+                let ac = *arch.get_gradient().get_ref_bl(0).get_ref(0, 1);
+                assert!((ac + 1.0).abs() < 1e-3, "gap {}: {} != -1", gap, ac);
+            }
+        }
+
+        #[test]
+        fn sigmoid_confidently_wrong_1() {
+
+            // setup
+            // one input and no hidden layer, the input is chosen so that the logit is `-gap`
+            let data: [f32; 2] = [1.0, 1.0];
+            let mut arch: Arch<Sigmoid, BinaryCrossEntropy, f32> =
+                Arch::with_seed(&data, 1, 1, 1, &[0usize; 0], Initializer::default(), 5);
+            let w = *arch.get_model().get_ref_wl(0).get_ref(0, 0);
+            let b = *arch.get_model().get_ref_bl(0).get_ref(0, 0);
+
+            for gap in [18.0, 30.0, 100.0] {
+                // action
+                arch.set_data(&[(-gap - b) / w, 1.0], 1);
+                arch.backprop();
+
+                // validation
+                // dL/dz is y - t, about -1 however saturated the sigmoid is
+                let ac = *arch.get_gradient().get_ref_bl(0).get_ref(0, 0);
+                assert!((ac + 1.0).abs() < 1e-3, "gap {}: {} != -1", gap, ac);
+            }
+        }
+
+        #[test]
+        fn maxout_hidden_1() {
+
+            // setup
+            let data = [
+                0.1, 0.9, 1.0,
+                0.8, 0.2, 0.0,
+            ];
+            let mut arch: Arch<Sigmoid> = Arch::new(&data, 2, 2, 1, &[Layer::with(3, Activation::Maxout(2))]);
+
+            // validation
+            assert_eq!(arch.get_model().get_ref_wl(0).get_col_count(), 6);
+            assert_eq!(arch.get_model().get_ref_wl(1).get_row_count(), 3);
+            assert_backprop_matches_finite_diff(&mut arch);
+        }
+
+        #[test]
+        fn train_with_target_cost_1() {
+
+            // setup
+            let and_data = [
+                0.0, 0.0, 0.0,
+                0.0, 1.0, 0.0,
+                1.0, 0.0, 0.0,
+                1.0, 1.0, 1.0,
+            ];
+            let mut arch: Arch<Sigmoid> = Arch::new(&and_data, 4, 2, 1, &[2]);
+            let config = TrainConfig {
+                rate: 1.0,
+                epochs: 100 * 1000,
+                batch_size: Some(2),
+                target_cost: Some(1e-3),
+                ..TrainConfig::default()
+            };
+
+            // action
+            let cost = arch.train_with(&config);
+
+            // validation
+            assert!(cost <= 1e-3, "cost {} did not reach target", cost);
+        }
+
+        #[test]
+        fn train_invalid_config_1() {
+
+            // setup
+            let data = [0.0, 1.0, 1.0];
+            let mut arch: Arch<Sigmoid> = Arch::new(&data, 1, 2, 1, &[2]);
+            let empty = Dataset::new(Matrix::zero(0, 2), Matrix::zero(0, 1));
+            let no_batch = TrainConfig { batch_size: Some(0), ..TrainConfig::default() };
+            let no_log = TrainConfig { log_interval: Some(0), ..TrainConfig::default() };
+            let config = TrainConfig { epochs: 1, ..TrainConfig::default() };
+
+            // action
+            let no_batch = arch.try_train_with_optimizer(&no_batch, &mut Adam::default());
+            let no_log = arch.try_train_with_optimizer(&no_log, &mut Adam::default());
+            let empty = arch.try_train_on_with_optimizer(&empty, &config, &mut Adam::default());
+
+            // validation
+            assert!(matches!(no_batch, Err(NNError::InvalidConfig(msg)) if msg.contains("batch_size")));
+            assert!(matches!(no_log, Err(NNError::InvalidConfig(msg)) if msg.contains("log_interval")));
+            assert!(matches!(empty, Err(NNError::EmptyDataset)));
+        }
+
+        #[test]
+        fn activations_1() {
+
+            // setup
+            let data = [0.3, 0.7, 1.0];
+
+            // action
+            let relu: Arch<ReLU> = Arch::new(&data, 1, 2, 1, &[3, 2]);
+            let mixed: Arch<Sigmoid> = Arch::new(&data, 1, 2, 1, &[
+                Layer::with(3, Activation::ReLU),
+                Layer::new(2),
+            ]);
+
+            // validation
+            assert_eq!(relu.get_activations(), [Some(Activation::ReLU); 3]);
+            assert_eq!(mixed.get_activations(), [Some(Activation::ReLU), Some(Activation::Sigmoid), Some(Activation::Sigmoid)]);
+        }
+
+        /// Not one of the built-in activations.
+        struct Softsign;
+        impl ActivationFunction for Softsign {
+            fn activate_in<T: Float>(x: T) -> T {
+                x / (T::one() + x.abs())
+            }
+            fn derivative_in<T: Float>(x: T) -> T {
+                T::one() / (T::one() + x.abs()).powi(2)
+            }
+        }
+
+        /// Implemented the way it was before the `_in` functions existed.
+        struct F64Softsign;
+        impl ActivationFunction for F64Softsign {
+            fn activate(x: f64) -> f64 {
+                x / (1.0 + x.abs())
+            }
+            fn derivative(x: f64) -> f64 {
+                1.0 / (1.0 + x.abs()).powi(2)
+            }
+        }
+
+        /// Like `F64Softsign`, a loss with only the `f64` functions.
+        struct F64Mse;
+        impl Loss for F64Mse {
+            fn loss(output: &[f64], target: &[f64]) -> f64 {
+                output.iter().zip(target).map(|(y, t)| (y - t) * (y - t)).sum()
+            }
+            fn derivative(output: &[f64], target: &[f64], grad: &mut [f64]) {
+                for ((g, y), t) in grad.iter_mut().zip(output).zip(target) {
+                    *g = 2.0 * (y - t);
+                }
+            }
+        }
+
+        #[test]
+        fn custom_f64_only_1() {
+
+            // setup
+            let data = [0.3, 0.7, 0.5];
+            let mut f64_only: Arch<F64Softsign, F64Mse> = Arch::with_seed(&data, 1, 2, 1, &[3], Initializer::default(), 1);
+            let mut generic: Arch<Softsign> = Arch::with_seed(&data, 1, 2, 1, &[3], Initializer::default(), 1);
+
+            // action and validation
+            assert_eq!(f64_only.predict(&data[..2]), generic.predict(&data[..2]));
+            assert_eq!(f64_only.cost(), generic.cost());
+            assert_backprop_matches_finite_diff(&mut f64_only);
+        }
+
+        #[test]
+        fn custom_activation_1() {
+
+            // setup
+            let data = [0.3, 0.7, 0.5];
+            let mut arch: Arch<Softsign> = Arch::with_seed(&data, 1, 2, 1, &[3], Initializer::default(), 1);
+
+            // action
+            let output = arch.predict(&data[..2]);
+
+            // validation
+            let model = arch.get_model();
+            let mut expected = data[..2].to_vec();
+            for i in 0..model.get_count() {
+                let (wl, bl) = (model.get_ref_wl(i), model.get_ref_bl(i));
+                expected = (0..wl.get_col_count())
+                    .map(|j| {
+                        let z: f64 = expected.iter().enumerate().map(|(k, a)| a * wl.get_ref(k, j)).sum();
+                        Softsign::activate(z + bl.get_ref(0, j))
+                    })
+                    .collect();
+            }
+            for (ac, ex) in output.iter().zip(&expected) {
+                assert!((ac - ex).abs() < 1e-12, "{} != {}", ac, ex);
+            }
+            assert_eq!(arch.get_activations(), [None, None]);
+            assert_backprop_matches_finite_diff(&mut arch);
+            let path = std::env::temp_dir().join("feoho_nn_custom_activation.fenn");
+            assert!(matches!(arch.save(path), Err(NNError::Format(_))));
+        }
+
+        #[test]
+        fn train_with_batches_1() {
+
+            // setup
+            let or_data = [
+                0.0, 0.0, 0.0,
+                0.0, 1.0, 1.0,
+                1.0, 0.0, 1.0,
+                1.0, 1.0, 1.0,
+            ];
+            let mut arch: Arch<Sigmoid> = Arch::new(&or_data, 4, 2, 1, &[2]);
+            let config = TrainConfig {
+                rate: 1.0,
+                epochs: 10 * 1000,
+                batch_size: Some(1),
+                shuffle: true,
+                seed: Some(42),
+                target_cost: Some(1e-3),
+                ..TrainConfig::default()
+            };
+
+            // action
+            let cost = arch.train_with(&config);
+
+            // validation
+            assert!(cost <= 1e-3, "cost {} did not reach target", cost);
+        }
+
+        #[test]
+        fn with_seed_1() {
+
+            // setup
+            let or_data = [
+                0.0, 0.0, 0.0,
+                0.0, 1.0, 1.0,
+                1.0, 0.0, 1.0,
+                1.0, 1.0, 1.0,
+            ];
+            let config = TrainConfig {
+                rate: 1.0,
+                epochs: 100,
+                batch_size: Some(1),
+                ..TrainConfig::default()
+            };
+            let new = |seed| -> Arch<Sigmoid> {
+                Arch::with_seed(&or_data, 4, 2, 1, &[3], Initializer::HeNormal, seed)
+            };
+            let mut a = new(7);
+            let mut b = new(7);
+            let mut c = new(8);
+
+            // action
+            a.train_with(&config);
+            b.train_with(&config);
+            c.train_with(&config);
+
+            // validation
+            for i in 0..a.get_model().get_count() {
+                assert_eq!(a.get_model().get_ref_wl(i).get_data_ref(), b.get_model().get_ref_wl(i).get_data_ref());
+                assert_eq!(a.get_model().get_ref_bl(i).get_data_ref(), b.get_model().get_ref_bl(i).get_data_ref());
+            }
+            assert_ne!(a.get_model().get_ref_wl(0).get_data_ref(), c.get_model().get_ref_wl(0).get_data_ref());
+        }
+
+        #[test]
+        fn train_with_optimizer_1() {
+
+            // setup
+            let or_data = [
+                0.0, 0.0, 0.0,
+                0.0, 1.0, 1.0,
+                1.0, 0.0, 1.0,
+                1.0, 1.0, 1.0,
+            ];
+            let mut arch: Arch<Sigmoid> = Arch::new(&or_data, 4, 2, 1, &[2]);
+            let config = TrainConfig {
+                rate: 1e-1,
+                epochs: 5 * 1000,
+                target_cost: Some(1e-3),
+                ..TrainConfig::default()
+            };
+
+            // action
+            let cost = arch.train_with_optimizer(&config, &mut Adam::default());
+
+            // validation
+            assert!(cost <= 1e-3, "cost {} did not reach target", cost);
+        }
+
+        #[test]
+        fn f32_train_1() {
+
+            // setup
+            let or_data: [f32; 12] = [
+                0.0, 0.0, 0.0,
+                0.0, 1.0, 1.0,
+                1.0, 0.0, 1.0,
+                1.0, 1.0, 1.0,
+            ];
+            let mut arch: Arch<Sigmoid, Mse, f32> = Arch::with_seed(&or_data, 4, 2, 1, &[2], Initializer::default(), 3);
+            let config = TrainConfig {
+                rate: 1e-1,
+                epochs: 5 * 1000,
+                target_cost: Some(1e-3),
+                ..TrainConfig::default()
+            };
+
+            // action
+            let cost = arch.train_with_optimizer(&config, &mut Adam::default());
+
+            // validation
+            assert!(cost <= 1e-3, "cost {} did not reach target", cost);
+        }
+
+        #[test]
+        fn f32_matches_f64_1() {
+
+            // setup
+            // the same seed draws the same weights, only rounded to f32
+            let data = [0.3, 0.7, 1.0, 0.9, 0.1, 0.0];
+            let data_f32 = data.map(|x| x as f32);
+            let mut arch: Arch<Sigmoid> = Arch::with_seed(&data, 2, 2, 1, &[3, 2], Initializer::default(), 9);
+            let mut arch_f32: Arch<Sigmoid, Mse, f32> = Arch::with_seed(&data_f32, 2, 2, 1, &[3, 2], Initializer::default(), 9);
+
+            // action
+            arch.backprop();
+            arch_f32.backprop();
+
+            // validation
+            assert!((arch.cost() - arch_f32.cost() as f64).abs() < 1e-5);
+            let (gradient, gradient_f32) = (arch.get_gradient(), arch_f32.get_gradient());
+            for i in 0..gradient.get_count() {
+                let wl = gradient.get_ref_wl(i).get_data_ref().iter().zip(gradient_f32.get_ref_wl(i).get_data_ref());
+                let bl = gradient.get_ref_bl(i).get_data_ref().iter().zip(gradient_f32.get_ref_bl(i).get_data_ref());
+                for (ex, ac) in wl.chain(bl) {
+                    assert!((*ac as f64 - ex).abs() < 1e-5, "layer {}: {} != {}", i, ac, ex);
+                }
+            }
+        }
+
+        #[test]
+        fn predict_1() {
+
+            // setup
+            let data = [
+                0.1, 0.9, 1.0, 0.0,
+                0.8, 0.2, 0.0, 1.0,
+                0.5, 0.5, 1.0, 0.0,
+            ];
+            let mut arch: Arch<Softmax> = Arch::new(&data, 3, 2, 2, &[Layer::with(4, Activation::Tanh), Layer::with(3, Activation::Maxout(2))]);
+            let inputs = Matrix::from(3, 2, 4, &data);
+            let expected: Vec<Vec<f64>> = (0..3).map(|row| arch.predict(inputs.get_row_ref(row))).collect();
+
+            // action
+            let batch = arch.predict_batch(&inputs);
+
+            // validation
+            assert_eq!(batch.get_row_count(), 3);
+            assert_eq!(batch.get_col_count(), 2);
+            for (row, expected) in expected.iter().enumerate() {
+                for (ac, ex) in batch.get_row_ref(row).iter().zip(expected) {
+                    assert!((ac - ex).abs() < 1e-12, "row {}: {} != {}", row, ac, ex);
+                }
+            }
+            // the forward pass used for training gives the same output
+            for (row, expected) in expected.iter().enumerate() {
+                arch.set_data(&data[row * 4..(row + 1) * 4], 1);
+                arch.cost();
+                for (ac, ex) in arch.get_model().get_output().get_row_ref(0).iter().zip(expected) {
+                    assert!((ac - ex).abs() < 1e-12, "row {}: {} != {}", row, ac, ex);
+                }
+            }
+        }
+
+        #[test]
+        fn row_wise_hidden_layer_1() {
+
+            // setup
+            let data = [0.1, 0.9, 1.0, 0.0];
+
+            // action
+            let softmax = Arch::<Softmax>::try_new(&data, 1, 2, 2, &[3]);
+            let log_softmax = Arch::<LogSoftmax, NegativeLogLikelihood>::try_new(&data, 1, 2, 2, &[Layer::with(3, Activation::ReLU), Layer::new(2)]);
+            let explicit = Arch::<Softmax>::try_new(&data, 1, 2, 2, &[Layer::with(3, Activation::ReLU)]);
+
+            // validation
+            assert!(matches!(softmax, Err(NNError::InvalidLayerSpec(msg)) if msg.contains("hidden layer 0")));
+            assert!(matches!(log_softmax, Err(NNError::InvalidLayerSpec(msg)) if msg.contains("hidden layer 1")));
+            let explicit = explicit.unwrap();
+            assert_eq!(explicit.get_activations(), [Some(Activation::ReLU), Some(Activation::Softmax)]);
+        }
+
+        #[test]
+        fn predict_shared_1() {
+
+            // setup
+            let xor_data = [
+                0.0, 0.0, 0.0,
+                0.0, 1.0, 1.0,
+                1.0, 0.0, 1.0,
+                1.0, 1.0, 0.0,
+            ];
+            let arch: Arch<Sigmoid> = Arch::with_seed(&xor_data, 4, 2, 1, &[3], Initializer::default(), 4);
+            let expected = arch.predict(&[1.0, 0.0]);
+
+            // action
+            let outputs: Vec<Vec<f64>> = std::thread::scope(|scope| {
+                let handles: Vec<_> = (0..4).map(|_| scope.spawn(|| arch.predict(&[1.0, 0.0]))).collect();
+                handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+            });
+
+            // validation
+            for output in outputs {
+                assert_eq!(output, expected);
+            }
+            assert!(matches!(arch.try_predict(&[1.0]), Err(NNError::ShapeMismatch { op: "predict", .. })));
+        }
+
+        #[test]
+        fn batch_gradient_matches_per_row_1() {
+            use rand::{rngs::StdRng, Rng, SeedableRng};
+
+            // setup
+            // large enough for the `parallel` feature to split the batch across threads
+            let rows = 200;
+            let mut rng = StdRng::seed_from_u64(5);
+            let data: Vec<f64> = (0..rows * 4).map(|_| rng.gen_range(0.0..1.0)).collect();
+            let mut arch: Arch<Sigmoid> = Arch::with_seed(&data, rows, 3, 1, &[4, 3], Initializer::default(), 1);
+            let mut expected_cost = 0.0;
+            let mut expected_gradient: Vec<Vec<f64>> = Vec::new();
+
+            // action
+            let cost = arch.cost();
+            arch.backprop();
+            let gradient = arch.get_gradient().clone();
+            for row in 0..rows {
+                arch.set_data(&data[row * 4..(row + 1) * 4], 1);
+                expected_cost += arch.cost() / rows as f64;
+                arch.backprop();
+                let g = arch.get_gradient();
+                let params: Vec<f64> = (0..g.get_count())
+                    .flat_map(|i| g.get_ref_wl(i).get_data_ref().iter().chain(g.get_ref_bl(i).get_data_ref()).copied())
+                    .collect();
+                if expected_gradient.is_empty() {
+                    expected_gradient.push(vec![0.0; params.len()]);
+                }
+                for (ex, p) in expected_gradient[0].iter_mut().zip(&params) {
+                    *ex += p / rows as f64;
+                }
+            }
+
+            // validation
+            assert!((cost - expected_cost).abs() < 1e-12, "{} != {}", cost, expected_cost);
+            let actual: Vec<f64> = (0..gradient.get_count())
+                .flat_map(|i| gradient.get_ref_wl(i).get_data_ref().iter().chain(gradient.get_ref_bl(i).get_data_ref()).copied())
+                .collect();
+            for (ac, ex) in actual.iter().zip(&expected_gradient[0]) {
+                assert!((ac - ex).abs() < 1e-12, "{} != {}", ac, ex);
+            }
+        }
+
+        #[test]
+        fn dataset_evaluate_1() {
+
+            // setup
+            let xor_data = [
+                0.0, 0.0, 0.0,
+                0.0, 1.0, 1.0,
+                1.0, 0.0, 1.0,
+                1.0, 1.0, 0.0,
+            ];
+            let dataset = Dataset::from_interleaved(&xor_data, 4, 2, 1);
+            let mut arch: Arch<Sigmoid> = Arch::with_seed(&xor_data, 4, 2, 1, &[3], Initializer::default(), 2);
+            let mut from_dataset: Arch<Sigmoid> = Arch::from_dataset_with_seed(dataset.clone(), &[3], Initializer::default(), 2);
+
+            // action
+            let cost = arch.cost();
+            let evaluated = arch.evaluate(&dataset);
+            let predicted = arch.predict_dataset(&dataset);
+
+            // validation
+            assert!((cost - evaluated).abs() < 1e-12, "{} != {}", cost, evaluated);
+            assert_eq!(from_dataset.cost(), cost);
+            assert_eq!(predicted.get_data_ref(), arch.predict_batch(dataset.get_inputs().view()).get_data_ref());
+            let wrong = Dataset::from_arrays(&[0.0, 1.0, 2.0], &[0.0], 1);
+            assert!(matches!(arch.try_evaluate(&wrong), Err(NNError::ShapeMismatch { op: "dataset", .. })));
+            let empty = Dataset::new(Matrix::zero(0, 2), Matrix::zero(0, 1));
+            assert!(matches!(arch.try_evaluate(&empty), Err(NNError::EmptyDataset)));
+        }
+
+        #[test]
+        fn train_on_dataset_1() {
+
+            // setup
+            let and_data = [
+                0.0, 0.0, 0.0,
+                0.0, 1.0, 0.0,
+                1.0, 0.0, 0.0,
+                1.0, 1.0, 1.0,
+            ];
+            let train = Dataset::from_interleaved(&and_data, 4, 2, 1);
+            let test = Dataset::from_arrays(&[1.0, 1.0, 0.0, 1.0], &[1.0, 0.0], 2);
+            let or_data = Dataset::from_arrays(&[0.0, 0.0, 0.0, 1.0], &[0.0, 1.0], 2);
+            let mut arch: Arch<Sigmoid> = Arch::from_dataset_with_seed(or_data, &[2], Initializer::default(), 3);
+            let config = TrainConfig {
+                rate: 1.0,
+                epochs: 100 * 1000,
+                target_cost: Some(1e-3),
+                ..TrainConfig::default()
+            };
+
+            // action
+            let cost = arch.train_on(&train, &config);
+
+            // validation
+            assert!(cost <= 1e-3, "cost {} did not reach target", cost);
+            assert_eq!(arch.get_dataset().get_row_count(), 2);
+            assert!(arch.evaluate(&test) < 1e-2);
+            arch.set_dataset(train);
+            assert!((arch.cost() - cost).abs() < 1e-12);
+        }
+
+        #[test]
+        fn set_dataset_errors_1() {
+
+            // setup
+            let data = [0.0, 1.0, 1.0];
+            let mut arch: Arch<Sigmoid> = Arch::new(&data, 1, 2, 1, &[2]);
+
+            // action
+            let wrong = arch.try_set_dataset(Dataset::from_arrays(&[1.0], &[1.0], 1));
+            let empty = arch.try_set_dataset(Dataset::new(Matrix::zero(0, 2), Matrix::zero(0, 1)));
+
+            // validation
+            assert!(matches!(wrong, Err(NNError::ShapeMismatch { op: "dataset", .. })));
+            assert!(empty.is_ok());
+            assert!(matches!(arch.try_cost(), Err(NNError::EmptyDataset)));
+            assert!(matches!(arch.try_backprop(), Err(NNError::EmptyDataset)));
+            assert!(matches!(arch.try_finite_diff(1e-6), Err(NNError::EmptyDataset)));
+        }
+
+        #[test]
+        #[should_panic(expected = "dataset is empty")]
+        fn backprop_without_dataset_1() {
+            let arch: Arch<Sigmoid> = Arch::new(&[0.0, 1.0, 1.0], 1, 2, 1, &[2]);
+            let path = std::env::temp_dir().join("feoho_nn_backprop_without_dataset.fenn");
+            arch.save(&path).unwrap();
+            Arch::<Sigmoid>::load(&path).unwrap().backprop();
+        }
+    }
+
+    pub mod dataset {
+        use feoho_nn::{Column, CsvOptions, Dataset, Matrix, MissingValues, NNError};
+
+        #[test]
+        fn from_interleaved_1() {
+
+            // setup
+            let data = [
+                1.0, 2.0, 3.0,
+                4.0, 5.0, 6.0,
+            ];
+
+            // action
+            let dataset = Dataset::from_interleaved(&data, 2, 2, 1);
+
+            // validation
+            assert_eq!(dataset.get_row_count(), 2);
+            assert_eq!(dataset.get_inputs().get_data_ref(), &[1.0, 2.0, 4.0, 5.0]);
+            assert_eq!(dataset.get_targets().get_data_ref(), &[3.0, 6.0]);
+            assert!(dataset.get_input_names().is_none());
+        }
+
+        #[test]
+        fn from_arrays_1() {
+
+            // setup
+            let inputs = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+            let targets = [0.0, 1.0, 1.0, 0.0, 0.5, 0.5];
+
+            // action
+            let dataset = Dataset::from_arrays(&inputs, &targets, 3).with_names(vec!["a", "b"], vec!["x", "y"]);
+            let (train, test) = dataset.split_at_row(2);
+
+            // validation
+            assert_eq!(dataset.get_input_col_count(), 2);
+            assert_eq!(dataset.get_target_col_count(), 2);
+            assert_eq!(train.get_inputs().get_data_ref(), &[1.0, 2.0, 3.0, 4.0]);
+            assert_eq!(test.get_targets().get_data_ref(), &[0.5, 0.5]);
+            assert_eq!(test.get_input_names().unwrap(), &["a".to_string(), "b".to_string()]);
+        }
+
+        #[test]
+        fn errors_1() {
+
+            // setup
+            let inputs: Matrix = Matrix::zero(3, 2);
+            let targets: Matrix = Matrix::zero(2, 1);
+
+            // action
+            let mismatch = Dataset::try_new(inputs, targets);
+            let short = Dataset::try_from_interleaved(&[1.0, 2.0], 1, 2, 1);
+            let huge = Dataset::try_from_interleaved(&[1.0, 2.0], usize::MAX, 1, 1);
+            let wide = Dataset::try_from_interleaved(&[1.0, 2.0], 1, usize::MAX, 1);
+            let uneven = Dataset::try_from_arrays(&[1.0, 2.0, 3.0], &[1.0, 2.0], 2);
+            let empty = Dataset::<f64>::try_from_arrays(&[], &[], 0);
+            let names = Dataset::from_arrays(&[1.0, 2.0], &[1.0], 1).try_with_names(vec!["a"], vec!["x"]);
+
+            // validation
+            assert!(matches!(mismatch, Err(NNError::ShapeMismatch { op: "Dataset::new", .. })));
+            assert!(matches!(short, Err(NNError::ShapeMismatch { op: "view", .. })));
+            assert!(matches!(huge, Err(NNError::ShapeMismatch { op: "view", .. })));
+            assert!(matches!(wide, Err(NNError::ShapeMismatch { op: "Dataset::from_interleaved", .. })));
+            assert!(matches!(uneven, Err(NNError::ShapeMismatch { op: "Dataset::from_arrays", .. })));
+            assert!(matches!(empty, Err(NNError::EmptyDataset)));
+            assert!(matches!(names, Err(NNError::ShapeMismatch { op: "Dataset::with_names", .. })));
+        }
+
+        #[test]
+        fn from_csv_1() {
+
+            // setup
+            let path = std::env::temp_dir().join(format!("feoho_nn_iris_{}.csv", std::process::id()));
+            std::fs::write(
+                &path,
+                "sepal,petal,species,id\n\
+                 5.1,1.4,setosa,1\n\
+                 7.0,4.7,versicolor,2\n\
+                 \n\
+                 6.3,6.0,\"virginica\",3\n\
+                 4.9,1.4,setosa,4\n",
+            )
+            .unwrap();
+            let options = CsvOptions {
+                inputs: vec![Column::from("petal"), Column::from(0)],
+                targets: vec![Column::from("species")],
+                ..CsvOptions::default()
+            };
+
+            // action
+            let dataset: Dataset = Dataset::from_csv(&path, &options).unwrap();
+            std::fs::remove_file(&path).unwrap();
+
+            // validation
+            assert_eq!(dataset.get_row_count(), 4);
+            assert_eq!(dataset.get_inputs().get_data_ref(), &[1.4, 5.1, 4.7, 7.0, 6.0, 6.3, 1.4, 4.9]);
+            assert_eq!(dataset.get_targets().get_data_ref(), &[0.0, 1.0, 2.0, 0.0]);
+            assert_eq!(dataset.get_input_names().unwrap(), &["petal".to_string(), "sepal".to_string()]);
+            assert_eq!(dataset.get_target_names().unwrap(), &["species".to_string()]);
+            assert_eq!(dataset.get_target_labels(0).unwrap(), &["setosa", "versicolor", "virginica"]);
+            assert!(dataset.get_input_labels(0).is_none());
+        }
+
+        #[test]
+        fn from_csv_no_header_1() {
+
+            // setup
+            let text = "# exported values\n1;\"a;b\";0.5\n2;c;1.5\n";
+            let options = CsvOptions {
+                targets: vec![Column::from(0)],
+                has_header: false,
+                skip_lines: 1,
+                delimiter: ';',
+                ..CsvOptions::default()
+            };
+
+            // action
+            let dataset: Dataset = Dataset::from_csv_str(text, &options).unwrap();
+
+            // validation
+            assert_eq!(dataset.get_inputs().get_data_ref(), &[0.0, 0.5, 1.0, 1.5]);
+            assert_eq!(dataset.get_targets().get_data_ref(), &[1.0, 2.0]);
+            assert_eq!(dataset.get_input_labels(0).unwrap(), &["a;b", "c"]);
+            assert!(dataset.get_input_names().is_none());
+        }
+
+        #[test]
+        fn from_csv_missing_1() {
+
+            // setup
+            let text = "x,y,label\n1,2,0\n3,,1\nNA,4,1\n5,6,0\n";
+            let options = |missing| CsvOptions {
+                targets: vec![Column::from("label")],
+                missing,
+                ..CsvOptions::default()
+            };
+
+            // action
+            let error = Dataset::<f64>::from_csv_str(text, &options(MissingValues::Error));
+            let dropped: Dataset = Dataset::from_csv_str(text, &options(MissingValues::Drop)).unwrap();
+            let filled: Dataset = Dataset::from_csv_str(text, &options(MissingValues::Fill(-1.0))).unwrap();
+
+            // validation
+            match error {
+                Err(NNError::Format(msg)) => assert!(msg.contains("line 3") && msg.contains("`y`"), "{}", msg),
+                other => panic!("expected a format error, got {:?}", other),
+            }
+            assert_eq!(dropped.get_inputs().get_data_ref(), &[1.0, 2.0, 5.0, 6.0]);
+            assert_eq!(dropped.get_targets().get_data_ref(), &[0.0, 0.0]);
+            assert_eq!(filled.get_inputs().get_data_ref(), &[1.0, 2.0, 3.0, -1.0, -1.0, 4.0, 5.0, 6.0]);
+        }
+
+        #[test]
+        fn from_csv_errors_1() {
+
+            // setup
+            let text = "a,b\n1,2\n3\n";
+            let select = |column: Column, has_header| CsvOptions {
+                targets: vec![column],
+                has_header,
+                ..CsvOptions::default()
+            };
+
+            // action
+            let fields = Dataset::<f64>::from_csv_str(text, &select(Column::from(1), true));
+            let unknown = Dataset::<f64>::from_csv_str("a,b\n1,2\n", &select(Column::from("c"), true));
+            let no_header = Dataset::<f64>::from_csv_str("1,2\n", &select(Column::from("a"), false));
+            let range = Dataset::<f64>::from_csv_str("1,2\n", &select(Column::from(2), false));
+            let empty = Dataset::<f64>::from_csv_str("a,b\n\n", &CsvOptions::default());
+            let missing = Dataset::<f64>::from_csv_str("a,b\n1,\n", &select(Column::from(1), true));
+            let io = Dataset::<f64>::from_csv("/nonexistent/feoho_nn.csv", &CsvOptions::default());
+
+            // validation
+            for (result, expected) in [
+                (fields, "expected 2 fields, found 1"),
+                (unknown, "no column named `c`"),
+                (no_header, "no header"),
+                (range, "out of range"),
+            ] {
+                match result {
+                    Err(NNError::Format(msg)) => assert!(msg.contains(expected), "{}", msg),
+                    other => panic!("expected a format error, got {:?}", other),
+                }
+            }
+            assert!(matches!(empty, Err(NNError::EmptyDataset)));
+            assert!(matches!(missing, Err(NNError::Format(_))));
+            assert!(matches!(io, Err(NNError::Io(_))));
+        }
+
+        /// IDX file of unsigned bytes with the given sizes.
+        fn idx(sizes: &[u32], data: &[u8]) -> Vec<u8> {
+            let mut bytes = vec![0, 0, 0x08, sizes.len() as u8];
+            for size in sizes {
+                bytes.extend_from_slice(&size.to_be_bytes());
+            }
+            bytes.extend_from_slice(data);
+            bytes
+        }
+
+        #[test]
+        fn from_idx_1() {
+
+            // setup
+            let dir = std::env::temp_dir();
+            let images_path = dir.join(format!("feoho_nn_images_{}.idx", std::process::id()));
+            let labels_path = dir.join(format!("feoho_nn_labels_{}.idx", std::process::id()));
+            std::fs::write(&images_path, idx(&[3, 2, 2], &[0, 255, 51, 102, 255, 255, 0, 0, 0, 0, 0, 153])).unwrap();
+            std::fs::write(&labels_path, idx(&[3], &[2, 0, 1])).unwrap();
+
+            // action
+            let dataset: Dataset = Dataset::from_idx(&images_path, &labels_path, 3).unwrap();
+            std::fs::remove_file(&images_path).unwrap();
+            std::fs::remove_file(&labels_path).unwrap();
+
+            // validation
+            assert_eq!(dataset.get_row_count(), 3);
+            assert_eq!(dataset.get_input_col_count(), 4);
+            assert_eq!(dataset.get_inputs().get_row_ref(0), &[0.0, 1.0, 0.2, 0.4]);
+            assert_eq!(dataset.get_inputs().get_row_ref(2), &[0.0, 0.0, 0.0, 0.6]);
+            assert_eq!(dataset.get_targets().get_data_ref(), &[0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+        }
+
+        #[test]
+        fn from_idx_errors_1() {
+
+            // setup
+            let images = idx(&[2, 1, 2], &[1, 2, 3, 4]);
+            let labels = idx(&[2], &[0, 1]);
+            let mut magic = images.clone();
+            magic[0] = 1;
+            let mut kind = images.clone();
+            kind[2] = 0x0D;
+
+            // action
+            let cases = [
+                (Dataset::<f64>::from_idx_bytes(&magic, &labels, 2), "magic number"),
+                (Dataset::<f64>::from_idx_bytes(&kind, &labels, 2), "type 0x0d"),
+                (Dataset::<f64>::from_idx_bytes(&labels, &labels, 2), "1 dims, expected 3"),
+                (Dataset::<f64>::from_idx_bytes(&images[..10], &labels, 2), "too short"),
+                (Dataset::<f64>::from_idx_bytes(&images[..images.len() - 1], &labels, 2), "3 bytes of data"),
+                (Dataset::<f64>::from_idx_bytes(&images, &idx(&[1], &[0]), 2), "2 images but 1 labels"),
+                (Dataset::<f64>::from_idx_bytes(&images, &labels, 1), "label 1 of item 1"),
+            ];
+
+            // validation
+            for (result, expected) in cases {
+                match result {
+                    Err(NNError::Format(msg)) => assert!(msg.contains(expected), "{}", msg),
+                    other => panic!("expected a format error, got {:?}", other),
+                }
+            }
+            assert!(matches!(
+                Dataset::<f64>::from_idx_bytes(&idx(&[0, 1, 2], &[]), &idx(&[0], &[]), 2),
+                Err(NNError::EmptyDataset)
+            ));
+            let huge = idx(&[u32::MAX, u32::MAX, u32::MAX], &[]);
+            assert!(matches!(
+                Dataset::<f64>::from_idx_bytes(&huge, &labels, 2),
+                Err(NNError::Format(msg)) if msg.contains("too large")
+            ));
+        }
+    }
+
+    pub mod activation {
+        use feoho_nn::{Activation, Matrix, Maxout, Softmax, VectorActivation};
+
+        #[test]
+        fn softmax_1() {
+
+            // setup
+            let mut matrix = Matrix::from(2, 3, 3, &[1.0, 2.0, 3.0, 1000.0, 1000.0, 1000.0]);
+
+            // action
+            matrix.activate_with(Activation::Softmax);
+
+            // validation
+            let sum: f64 = (1.0f64).exp() + (2.0f64).exp() + (3.0f64).exp();
+            let expected = [1.0f64.exp() / sum, 2.0f64.exp() / sum, 3.0f64.exp() / sum];
+            for (ac, ex) in matrix.get_row_ref(0).iter().zip(expected) {
+                assert!((ac - ex).abs() < 1e-12);
+            }
+            // large inputs must not overflow
+            for ac in matrix.get_row_ref(1) {
+                assert!((ac - 1.0 / 3.0).abs() < 1e-12);
+            }
+        }
+
+        #[test]
+        fn softmax_backward_1() {
+
+            // setup
+            let z = [0.5, -1.0, 2.0];
+            let da = [0.3, -0.7, 0.2];
+            let mut a = [0.0; 3];
+            Softmax::activate_row(&z, &mut a);
+
+            // action
+            let mut dz = [0.0; 3];
+            Softmax::backward_row(&z, &a, &da, &mut dz);
+
+            // validation
+            // compare with the full jacobian, J[i][j] = a[i] * (delta(i, j) - a[j])
+            for i in 0..3 {
+                let mut ex = 0.0;
+                for j in 0..3 {
+                    let delta = if i == j { 1.0 } else { 0.0 };
+                    ex += a[j] * (delta - a[i]) * da[j];
+                }
+                assert!((dz[i] - ex).abs() < 1e-12, "{}: {} != {}", i, dz[i], ex);
+            }
+        }
+
+        #[test]
+        fn maxout_1() {
+
+            // setup
+            let z = [1.0, 3.0, -2.0, -5.0];
+            let mut a = [0.0; 2];
+
+            // action
+            Maxout::<2>::activate_row(&z, &mut a);
+            let mut dz = [0.0; 4];
+            Maxout::<2>::backward_row(&z, &a, &[0.5, 0.25], &mut dz);
+
+            // validation
+            assert_eq!(a, [3.0, -2.0]);
+            assert_eq!(dz, [0.0, 0.5, 0.25, 0.0]);
+        }
+    }
+
+    pub mod init {
+        use feoho_nn::{Initializer, Tensor};
+
+        #[test]
+        fn xavier_uniform_1() {
+
+            // setup
+            let mut tensor = Tensor::from(&[30, 20, 10]);
+            tensor.fill(1.0);
+
+            // action
+            tensor.initialize(Initializer::XavierUniform);
+
+            // validation
+            for (i, (fan_in, fan_out)) in [(30.0, 20.0), (20.0, 10.0)].iter().enumerate() {
+                let limit = (6.0f64 / (fan_in + fan_out)).sqrt();
+                for w in tensor.get_ref_wl(i).get_data_ref() {
+                    assert!(w.abs() <= limit, "{} outside of ±{}", w, limit);
+                }
+                for b in tensor.get_ref_bl(i).get_data_ref() {
+                    assert_eq!(*b, 0.0);
+                }
+            }
+        }
+
+        #[test]
+        fn he_normal_1() {
+
+            // setup
+            let mut tensor = Tensor::from(&[200, 100]);
+
+            // action
+            tensor.initialize(Initializer::HeNormal);
+
+            // validation
+            let w = tensor.get_ref_wl(0).get_data_ref();
+            let n = w.len() as f64;
+            let mean = w.iter().sum::<f64>() / n;
+            let std = (w.iter().map(|w| (w - mean) * (w - mean)).sum::<f64>() / n).sqrt();
+            let expected = (2.0f64 / 200.0).sqrt();
+            assert!(mean.abs() < 0.01, "mean {}", mean);
+            assert!((std - expected).abs() < 0.05 * expected, "std {} != {}", std, expected);
+        }
+
+        #[test]
+        fn orthogonal_1() {
+
+            // setup
+            // tall and wide layers
+            let mut tensor = Tensor::from(&[6, 3, 5]);
+
+            // action
+            tensor.initialize(Initializer::Orthogonal);
+
+            // validation
+            let tall = tensor.get_ref_wl(0);
+            for a in 0..3 {
+                for b in 0..3 {
+                    let dot: f64 = (0..6).map(|k| tall.get_ref(k, a) * tall.get_ref(k, b)).sum();
+                    let expected = if a == b { 1.0 } else { 0.0 };
+                    assert!((dot - expected).abs() < 1e-9, "cols {} {}: {}", a, b, dot);
+                }
+            }
+            let wide = tensor.get_ref_wl(1);
+            for a in 0..3 {
+                for b in 0..3 {
+                    let dot: f64 = wide.get_row_ref(a).iter().zip(wide.get_row_ref(b)).map(|(x, y)| x * y).sum();
+                    let expected = if a == b { 1.0 } else { 0.0 };
+                    assert!((dot - expected).abs() < 1e-9, "rows {} {}: {}", a, b, dot);
+                }
+            }
+        }
+    }
+
+    pub mod loss {
+        use feoho_nn::{BinaryCrossEntropy, CategoricalCrossEntropy, Hinge, Huber, Loss, Mae, Mse};
+
+        /// Compares `L::derivative` with a central difference of `L::loss`.
+        fn check_derivative<L: Loss>(output: &[f64], target: &[f64]) {
+            let eps = 1e-6;
+            let mut grad = vec![0.0; output.len()];
+            L::derivative(output, target, &mut grad);
+            for (j, ac) in grad.iter().enumerate() {
+                let mut plus = output.to_vec();
+                plus[j] += eps;
+                let mut minus = output.to_vec();
+                minus[j] -= eps;
+                let ex = (L::loss(&plus, target) - L::loss(&minus, target)) / (2.0 * eps);
+                assert!((ac - ex).abs() < 1e-4, "{}: {} != {}", j, ac, ex);
+            }
+        }
+
+        #[test]
+        fn mse_1() {
+            let mut grad = [0.0; 2];
+            Mse::derivative(&[1.0, 2.0], &[0.0, 0.0], &mut grad);
+            assert_eq!(Mse::loss(&[1.0, 2.0], &[0.0, 0.0]), 5.0);
+            assert_eq!(grad, [2.0, 4.0]);
+        }
+
+        #[test]
+        fn huber_1() {
+            // squared below delta, linear above
+            assert_eq!(Huber::loss(&[0.5], &[0.0]), 0.125);
+            assert_eq!(Huber::loss(&[3.0], &[0.0]), 2.5);
+        }
+
+        #[test]
+        fn hinge_1() {
+            assert_eq!(Hinge::loss(&[2.0, 0.5], &[1.0, 1.0]), 0.5);
+            assert_eq!(Hinge::loss(&[0.5], &[-1.0]), 1.5);
+        }
+
+        #[test]
+        fn derivative_1() {
+            let output = [0.2, 0.7, 0.1];
+            let target = [0.0, 1.0, 0.0];
+            check_derivative::<Mse>(&output, &target);
+            check_derivative::<Mae>(&output, &target);
+            check_derivative::<BinaryCrossEntropy>(&output, &target);
+            check_derivative::<CategoricalCrossEntropy>(&output, &target);
+            check_derivative::<Huber>(&[2.5, -0.3], &[0.0, 0.0]);
+            check_derivative::<Hinge>(&[0.3, -2.0], &[1.0, -1.0]);
+        }
+
+        #[test]
+        fn binary_cross_entropy_f32_saturated_1() {
+
+            // setup
+            // a sigmoid output rounds to exactly 1.0 or 0.0 in f32 from |x| of about 17 on
+            let outputs: [f32; 2] = [1.0, 0.0];
+            let targets: [f32; 2] = [0.0, 1.0];
+            let mut grad = [0.0f32; 2];
+
+            // action
+            let loss = BinaryCrossEntropy::loss_in(&outputs, &targets);
+            BinaryCrossEntropy::derivative_in(&outputs, &targets, &mut grad);
+
+            // validation
+            assert!(loss.is_finite() && loss > 0.0, "{}", loss);
+            assert!(grad.iter().all(|g| g.is_finite()), "{:?}", grad);
+            assert!(grad[0] > 0.0 && grad[1] < 0.0, "{:?}", grad);
+        }
+    }
+
+    pub mod error {
+        use feoho_nn::{Arch, Matrix, NNError, Sigmoid, Tensor};
+
+        #[test]
+        fn matrix_1() {
+            let a = Matrix::zero(2, 3);
+            let b = Matrix::zero(2, 3);
+            let mut dest = Matrix::zero(2, 3);
+
+            assert!(matches!(
+                dest.try_dot(&a, &b),
+                Err(NNError::ShapeMismatch { op: "dot", left: (2, 3), right: (2, 3) })
+            ));
+            assert!(matches!(
+                Matrix::zero(3, 2).try_add(&a),
+                Err(NNError::ShapeMismatch { op: "add", left: (3, 2), right: (2, 3) })
+            ));
+            assert!(matches!(
+                Matrix::try_from(2, 2, 2, &[1.0, 2.0, 3.0]),
+                Err(NNError::ShapeMismatch { op: "from", .. })
+            ));
+            assert!(matches!(
+                Matrix::try_from(usize::MAX, 2, 2, &[1.0, 2.0]),
+                Err(NNError::ShapeMismatch { op: "from", .. })
+            ));
+            assert!(matches!(
+                a.try_get_ref(2, 0),
+                Err(NNError::IndexOutOfBounds { index: (2, 0), shape: (2, 3) })
+            ));
+            assert!(dest.try_add(&b).is_ok());
+        }
+
+        #[test]
+        fn tensor_1() {
+            assert!(matches!(Tensor::try_from(&[2]), Err(NNError::InvalidLayerSpec(_))));
+            assert!(matches!(Tensor::try_from(&[2, 0, 1]), Err(NNError::InvalidLayerSpec(_))));
+            assert!(Tensor::try_from(&[2, 1]).is_ok());
+        }
+
+        #[test]
+        fn arch_1() {
+            let data = [0.0, 1.0, 1.0, 1.0, 0.0];
+            assert!(matches!(Arch::<Sigmoid>::try_new(&[], 0, 2, 1, &[2]), Err(NNError::EmptyDataset)));
+            assert!(matches!(Arch::<Sigmoid>::try_new(&data, 2, 2, 1, &[2]), Err(NNError::ShapeMismatch { .. })));
+            assert!(matches!(Arch::<Sigmoid>::try_new(&data, usize::MAX, 2, 1, &[2]), Err(NNError::ShapeMismatch { .. })));
+            assert!(matches!(Arch::<Sigmoid>::try_new(&data, 1, 2, 1, &[0]), Err(NNError::InvalidLayerSpec(_))));
+            assert!(matches!(Arch::<Sigmoid>::try_new(&data, 1, 0, 1, &[2]), Err(NNError::InvalidLayerSpec(_))));
+            assert!(matches!(Arch::<Sigmoid>::load("/nonexistent/feoho_nn.fenn"), Err(NNError::Io(_))));
+            assert!(Arch::<Sigmoid>::try_new(&data, 1, 2, 1, &[2]).is_ok());
+        }
+    }
+
+    pub mod format {
+        use std::{fs, path::PathBuf};
+
+        use feoho_nn::{Activation, Arch, Layer, NNError, Sigmoid, Softmax, Tensor};
+
+        fn temp_path(name: &str) -> PathBuf {
+            std::env::temp_dir().join(format!("feoho_nn_{}_{}.fenn", name, std::process::id()))
+        }
+
+        fn assert_same(a: &Tensor, b: &Tensor) {
+            assert_eq!(a.get_count(), b.get_count());
+            for i in 0..a.get_count() {
+                assert_eq!(a.get_ref_wl(i).get_data_ref(), b.get_ref_wl(i).get_data_ref());
+                assert_eq!(a.get_ref_bl(i).get_data_ref(), b.get_ref_bl(i).get_data_ref());
+            }
+        }
+
+        #[test]
+        fn tensor_save_load_1() {
+
+            // setup
+            let path = temp_path("tensor");
+            let mut tensor = Tensor::from(&[3, 4, 2]);
+            tensor.randomize();
+
+            // action
+            tensor.save(&path).unwrap();
+            let loaded = Tensor::load(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            // validation
+            assert_same(&tensor, &loaded);
+        }
+
+        #[test]
+        fn tensor_save_load_f32_1() {
+
+            // setup
+            // stored as f64, so an f32 tensor comes back unchanged
+            let path = temp_path("tensor_f32");
+            let mut tensor: Tensor<f32> = Tensor::from_in(&[3, 4, 2]);
+            tensor.randomize();
+
+            // action
+            tensor.save(&path).unwrap();
+            let loaded: Tensor<f32> = Tensor::load_in(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            // validation
+            for i in 0..tensor.get_count() {
+                assert_eq!(tensor.get_ref_wl(i).get_data_ref(), loaded.get_ref_wl(i).get_data_ref());
+                assert_eq!(tensor.get_ref_bl(i).get_data_ref(), loaded.get_ref_bl(i).get_data_ref());
+            }
+        }
+
+        #[test]
+        fn arch_save_load_1() {
+
+            // setup
+            let path = temp_path("arch");
+            let data = [0.0, 1.0, 1.0, 0.0];
+            let arch: Arch<Softmax> = Arch::new(&data, 1, 2, 2, &[Layer::with(3, Activation::Maxout(2))]);
+
+            // action
+            arch.save(&path).unwrap();
+            let loaded = Arch::<Softmax>::load(&path);
+            let wrong_output = Arch::<Sigmoid>::load(&path);
+            fs::remove_file(&path).unwrap();
+
+            // validation
+            let loaded = loaded.unwrap();
+            assert_eq!(loaded.get_activations(), [Some(Activation::Maxout(2)), Some(Activation::Softmax)]);
+            assert_same(arch.get_model(), loaded.get_model());
+            assert!(wrong_output.is_err());
+        }
+
+        #[test]
+        fn corrupted_1() {
+
+            // setup
+            let path = temp_path("corrupted");
+            let mut tensor = Tensor::from(&[2, 1]);
+            tensor.randomize();
+            tensor.save(&path).unwrap();
+            let bytes = fs::read(&path).unwrap();
+
+            // action
+            let mut flipped = bytes.clone();
+            flipped[20] ^= 0xff;
+            fs::write(&path, &flipped).unwrap();
+            let flipped = Tensor::load(&path);
+
+            fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+            let truncated = Tensor::load(&path);
+
+            let mut magic = bytes.clone();
+            magic[0] = b'X';
+            fs::write(&path, &magic).unwrap();
+            let magic = Tensor::load(&path);
+            fs::remove_file(&path).unwrap();
+
+            // validation
+            assert!(matches!(flipped, Err(NNError::Format(_))));
+            assert!(matches!(truncated, Err(NNError::Format(_))));
+            assert!(matches!(magic, Err(NNError::Format(_))));
+            assert_eq!(&bytes[..4], b"FENN");
+        }
+    }
+
+    pub mod npy {
+        use std::path::PathBuf;
+
+        use feoho_nn::{Matrix, NNError};
+
+        fn temp_path(name: &str, extension: &str) -> PathBuf {
+            std::env::temp_dir().join(format!("feoho_nn_{}_{}.{}", name, std::process::id(), extension))
+        }
+
+        /// `.npy` file with a version 1 header, like `np.save` writes it.
+        fn npy(descr: &str, fortran_order: &str, shape: &str, data: &[u8]) -> Vec<u8> {
+            let header = format!("{{'descr': '{}', 'fortran_order': {}, 'shape': {}, }}\n", descr, fortran_order, shape);
+            let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+            bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+            bytes.extend_from_slice(header.as_bytes());
+            bytes.extend_from_slice(data);
+            bytes
+        }
+
+        /// Zip archive of `(name, method, data)` entries, the data is stored as is and the crc left out.
+        fn zip(entries: &[(&str, u16, &[u8])]) -> Vec<u8> {
+            let (mut bytes, mut central) = (Vec::new(), Vec::new());
+            for (name, method, data) in entries {
+                let offset = bytes.len() as u32;
+                for (out, signature) in [(&mut bytes, 0x0403_4b50u32), (&mut central, 0x0201_4b50)] {
+                    out.extend_from_slice(&signature.to_le_bytes());
+                    if signature == 0x0201_4b50 {
+                        out.extend_from_slice(&[20, 0]);
+                    }
+                    out.extend_from_slice(&[20, 0, 0, 0]);
+                    out.extend_from_slice(&method.to_le_bytes());
+                    out.extend_from_slice(&[0; 8]);
+                    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+                    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+                    out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+                    out.extend_from_slice(&[0, 0]);
+                    if signature == 0x0201_4b50 {
+                        // comment length, disk, internal and external attributes
+                        out.extend_from_slice(&[0; 10]);
+                        out.extend_from_slice(&offset.to_le_bytes());
+                    }
+                    out.extend_from_slice(name.as_bytes());
+                }
+                bytes.extend_from_slice(data);
+            }
+            let (start, len) = (bytes.len() as u32, central.len() as u32);
+            bytes.extend_from_slice(&central);
+            bytes.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+            bytes.extend_from_slice(&[0; 4]);
+            for _ in 0..2 {
+                bytes.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+            }
+            bytes.extend_from_slice(&len.to_le_bytes());
+            bytes.extend_from_slice(&start.to_le_bytes());
+            bytes.extend_from_slice(&[0, 0]);
+            bytes
+        }
+
+        #[test]
+        fn write_read_1() {
+
+            // setup
+            // strided view over the first two cols of a 2x3 buffer
+            let matrix = Matrix::from(2, 2, 3, &[1.0, 2.0, 9.0, 3.0, 4.5, 9.0]);
+            let single: Matrix<f32> = Matrix::from_in(1, 3, 3, &[0.25, -1.0, 3.5]);
+            let path = temp_path("write_read", "npy");
+
+            // action
+            matrix.write_npy(&path).unwrap();
+            let loaded: Matrix = Matrix::read_npy(&path).unwrap();
+            let bytes = std::fs::read(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            let single_bytes = single.to_npy_bytes();
+
+            // validation
+            assert_eq!(loaded.get_row_count(), 2);
+            assert_eq!(loaded.get_col_count(), 2);
+            assert_eq!(loaded.get_data_ref(), &[1.0, 2.0, 3.0, 4.5]);
+            assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+            let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+            let header = std::str::from_utf8(&bytes[10..10 + header_len]).unwrap();
+            assert_eq!(header.trim_end(), "{'descr': '<f8', 'fortran_order': False, 'shape': (2, 2), }");
+            assert_eq!((10 + header_len) % 64, 0);
+            assert_eq!(bytes.len(), 10 + header_len + 4 * 8);
+            assert!(single_bytes.windows(5).any(|w| w == b"'<f4'"));
+            assert_eq!(Matrix::<f32>::from_npy_bytes(&single_bytes).unwrap().get_data_ref(), &[0.25, -1.0, 3.5]);
+        }
+
+        #[test]
+        fn read_1() {
+
+            // setup
+            let values: Vec<u8> = [1.5f32, 2.0, -3.0].iter().flat_map(|v| v.to_le_bytes()).collect();
+            let v1 = npy("<f4", "False", "(3,)", &values);
+            // same array with a version 2 header, its length is a u32
+            let mut v2 = b"\x93NUMPY\x02\x00".to_vec();
+            v2.extend_from_slice(&((v1.len() - 10 - values.len()) as u32).to_le_bytes());
+            v2.extend_from_slice(&v1[10..]);
+
+            // action
+            let from_v1: Matrix = Matrix::from_npy_bytes(&v1).unwrap();
+            let from_v2: Matrix = Matrix::from_npy_bytes(&v2).unwrap();
+
+            // validation
+            for matrix in [from_v1, from_v2] {
+                assert_eq!(matrix.get_row_count(), 1);
+                assert_eq!(matrix.get_col_count(), 3);
+                assert_eq!(matrix.get_data_ref(), &[1.5, 2.0, -3.0]);
+            }
+        }
+
+        #[test]
+        fn read_errors_1() {
+
+            // setup
+            let data = [0u8; 16];
+            let mut version = npy("<f8", "False", "(2,)", &data);
+            version[6] = 3;
+            let cases = [
+                (npy("<i8", "False", "(2,)", &data), "dtype `<i8` is not supported"),
+                (npy(">f8", "False", "(2,)", &data), "dtype `>f8` is not supported"),
+                (npy("<f8", "True", "(2,)", &data), "Fortran order"),
+                (npy("<f8", "False", "(1, 1, 2)", &data), "3-D arrays"),
+                (npy("<f8", "False", "(3,)", &data), "16 bytes of data for shape (1, 3)"),
+                (version, "version 3.0"),
+                (b"not numpy".to_vec(), "magic"),
+                (npy("<f8", "False", "(4294967296, 4294967296)", &data), "too large"),
+            ];
+
+            // action
+            let results: Vec<_> = cases.iter().map(|(bytes, _)| Matrix::<f64>::from_npy_bytes(bytes)).collect();
+
+            // validation
+            for (result, (_, expected)) in results.into_iter().zip(&cases) {
+                match result {
+                    Err(NNError::Format(msg)) => assert!(msg.contains(expected), "{}", msg),
+                    other => panic!("expected a format error, got {:?}", other),
+                }
+            }
+            assert!(matches!(Matrix::<f64>::read_npy(temp_path("missing", "npy")), Err(NNError::Io(_))));
+        }
+
+        #[test]
+        fn read_npz_1() {
+
+            // setup
+            let x: Matrix = Matrix::from(2, 2, 2, &[1.0, 2.0, 3.0, 4.0]);
+            let y: Matrix<f32> = Matrix::from_in(1, 2, 2, &[0.5, 0.25]);
+            let (x_bytes, y_bytes) = (x.to_npy_bytes(), y.to_npy_bytes());
+            let archive = zip(&[("x.npy", 0, &x_bytes), ("arr_0.npy", 0, &y_bytes), ("packed.npy", 8, &y_bytes)]);
+            let path = temp_path("read_npz", "npz");
+            std::fs::write(&path, &archive).unwrap();
+
+            // action
+            let loaded_x: Matrix = Matrix::read_npz(&path, "x").unwrap();
+            let loaded_y: Matrix = Matrix::read_npz(&path, "arr_0.npy").unwrap();
+            let missing = Matrix::<f64>::read_npz(&path, "z");
+            let compressed = Matrix::<f64>::read_npz(&path, "packed");
+            std::fs::remove_file(&path).unwrap();
+
+            // validation
+            assert_eq!(loaded_x.get_data_ref(), x.get_data_ref());
+            assert_eq!(loaded_y.get_data_ref(), &[0.5, 0.25]);
+            assert!(matches!(missing, Err(NNError::Format(msg)) if msg.contains("no array named `z`")));
+            assert!(matches!(compressed, Err(NNError::Format(msg)) if msg.contains("compressed")));
+            assert!(matches!(Matrix::<f64>::from_npz_bytes(b"plain bytes", "x"), Err(NNError::Format(_))));
+        }
+
+        #[test]
+        fn read_npz_zip64_overflow_1() {
+
+            // setup
+            let x: Matrix = Matrix::from(1, 1, 1, &[1.0]);
+            let mut archive = zip(&[("x.npy", 0, &x.to_npy_bytes())]);
+            // zip64 extra field with a compressed size of u64::MAX
+            let central = archive.windows(4).position(|w| w == 0x0201_4b50u32.to_le_bytes()).unwrap();
+            archive[central + 20..central + 24].copy_from_slice(&u32::MAX.to_le_bytes());
+            archive[central + 30..central + 32].copy_from_slice(&12u16.to_le_bytes());
+            let mut extra = vec![1, 0, 8, 0];
+            extra.extend_from_slice(&u64::MAX.to_le_bytes());
+            let name_end = central + 46 + "x.npy".len();
+            archive.splice(name_end..name_end, extra);
+
+            // action
+            let result = Matrix::<f64>::from_npz_bytes(&archive, "x");
+
+            // validation
+            assert!(matches!(result, Err(NNError::Format(msg)) if msg.contains("too short")));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub mod json {
+        use feoho_nn::{Activation, Arch, Layer, Matrix, Sigmoid, Tensor};
+
+        #[test]
+        fn matrix_1() {
+
+            // setup
+            // strided view over the first two cols of a 2x3 buffer
+            let matrix = Matrix::from(2, 2, 3, &[1.0, 2.0, 9.0, 3.0, 4.0, 9.0]);
+
+            // action
+            let json = serde_json::to_string(&matrix).unwrap();
+            let loaded: Matrix = serde_json::from_str(&json).unwrap();
+
+            // validation
+            assert_eq!(json, r#"{"rows":2,"cols":2,"stride":2,"data":[1.0,2.0,3.0,4.0]}"#);
+            assert_eq!(loaded.get_row_ref(0), &[1.0, 2.0]);
+            assert_eq!(loaded.get_row_ref(1), &[3.0, 4.0]);
+        }
+
+        #[test]
+        fn matrix_padded_1() {
+
+            // setup
+            // every row, including the last, carries the padding of the stride
+            let padded = r#"{"rows":2,"cols":2,"stride":3,"data":[1.0,2.0,9.0,3.0,4.0,9.0]}"#;
+
+            // action
+            let matrix: Matrix = serde_json::from_str(padded).unwrap();
+            let json = serde_json::to_string(&matrix).unwrap();
+            let loaded: Matrix = serde_json::from_str(&json).unwrap();
+
+            // validation
+            assert_eq!(json, r#"{"rows":2,"cols":2,"stride":2,"data":[1.0,2.0,3.0,4.0]}"#);
+            assert_eq!(loaded.get_row_ref(0), &[1.0, 2.0]);
+            assert_eq!(loaded.get_row_ref(1), &[3.0, 4.0]);
+        }
+
+        #[test]
+        fn matrix_invalid_1() {
+            let short = serde_json::from_str::<Matrix>(r#"{"rows":2,"cols":2,"stride":2,"data":[1.0,2.0,3.0]}"#);
+            let stride = serde_json::from_str::<Matrix>(r#"{"rows":1,"cols":2,"stride":1,"data":[1.0,2.0]}"#);
+            let strided = serde_json::from_str::<Matrix>(r#"{"rows":2,"cols":1,"stride":2,"data":[1.0,0.0,2.0]}"#);
+            let huge = serde_json::from_str::<Matrix>(r#"{"rows":18446744073709551615,"cols":1,"stride":2,"data":[]}"#);
+            assert!(short.is_err());
+            assert!(stride.is_err());
+            assert!(huge.unwrap_err().to_string().contains("too large"));
+            assert_eq!(strided.unwrap().get_row_ref(1), &[2.0]);
+        }
+
+        #[test]
+        fn arch_1() {
+
+            // setup
+            let data = [0.0, 1.0, 1.0];
+            let arch: Arch<Sigmoid> = Arch::new(&data, 1, 2, 1, &[Layer::with(3, Activation::ReLU)]);
+
+            // action
+            let json = serde_json::to_string(&arch).unwrap();
+            let loaded: Arch<Sigmoid> = serde_json::from_str(&json).unwrap();
+            let tensor: Tensor = serde_json::from_str(&serde_json::to_string(arch.get_model()).unwrap()).unwrap();
+
+            // validation
+            assert!(json.starts_with(r#"{"activations":["relu","sigmoid"],"model":{"layers":[2,3,1],"#));
+            assert_eq!(loaded.get_activations(), arch.get_activations());
+            for i in 0..arch.get_model().get_count() {
+                assert_eq!(loaded.get_model().get_ref_wl(i).get_data_ref(), arch.get_model().get_ref_wl(i).get_data_ref());
+                assert_eq!(tensor.get_ref_bl(i).get_data_ref(), arch.get_model().get_ref_bl(i).get_data_ref());
+            }
+        }
+    }
+
+    pub mod optimizer {
+        use feoho_nn::{Adam, AdamW, Momentum, Optimizer, Sgd, Tensor};
+
+        fn setup() -> (Tensor, Tensor) {
+            let mut model = Tensor::from(&[2, 1]);
+            model.fill(1.0);
+            let mut gradient = Tensor::from(&[2, 1]);
+            gradient.fill(0.5);
+            (model, gradient)
+        }
+
+        fn assert_params(model: &Tensor, expected: f64) {
+            for ac in model.get_ref_wl(0).get_data_ref().iter().chain(model.get_ref_bl(0).get_data_ref()) {
+                assert!((ac - expected).abs() < 1e-6, "{} != {}", ac, expected);
+            }
+        }
+
+        #[test]
+        fn sgd_1() {
+            let (mut model, gradient) = setup();
+            Sgd.step(&mut model, &gradient, 0.1);
+            assert_params(&model, 0.95);
+        }
+
+        #[test]
+        fn momentum_1() {
+            let (mut model, gradient) = setup();
+            let mut optimizer = Momentum::new(0.9);
+
+            // v = 0.5, then v = 0.9 * 0.5 + 0.5 = 0.95
+            optimizer.step(&mut model, &gradient, 0.1);
+            optimizer.step(&mut model, &gradient, 0.1);
+            assert_params(&model, 1.0 - 0.05 - 0.095);
+        }
+
+        #[test]
+        fn adam_1() {
+            let (mut model, gradient) = setup();
+
+            // bias correction makes the first step exactly `rate` in the direction of the gradient
+            Adam::default().step(&mut model, &gradient, 0.1);
+            assert_params(&model, 0.9);
+        }
+
+        #[test]
+        fn reused_on_other_shape_1() {
+            let mut optimizer = Adam::default();
+            let mut small = Tensor::from(&[2, 2, 1]);
+            let mut gradient = Tensor::from(&[2, 2, 1]);
+            gradient.fill(0.5);
+            optimizer.step(&mut small, &gradient, 0.1);
+
+            // the state of the 2-2-1 net must not be zipped against the 2-4-1 one
+            let mut model = Tensor::from(&[2, 4, 1]);
+            model.fill(1.0);
+            let mut gradient = Tensor::from(&[2, 4, 1]);
+            gradient.fill(0.5);
+            optimizer.step(&mut model, &gradient, 0.1);
+            for ac in model.get_ref_wl(0).get_data_ref() {
+                assert!((ac - 0.9).abs() < 1e-6, "{} != 0.9", ac);
+            }
+        }
+
+        #[test]
+        fn adamw_1() {
+            let (mut model, gradient) = setup();
+
+            // decay first: 1.0 * (1 - 0.1 * 0.5), then the adam step of 0.1
+            AdamW::new(0.9, 0.999, 1e-8, 0.5).step(&mut model, &gradient, 0.1);
+            assert_params(&model, 0.95 - 0.1);
+        }
     }
 }